@@ -23,7 +23,7 @@
 //! Common API for accessing RGB contract operation graph, including individual
 //! state transitions, genesis, outputs, assignments & single-use-seal data.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 use aluvm::library::{Lib, LibId};
 use amplify::confinement::ConfinedOrdMap;
@@ -33,7 +33,8 @@ use strict_types::TypeSystem;
 use super::EAnchor;
 use crate::{
     AssignmentType, AssignmentsRef, BundleId, ContractId, Genesis, GlobalState, GraphSeal,
-    Metadata, OpFullType, OpId, Operation, Schema, Transition, TransitionBundle, TypedAssigns,
+    KnownTransition, Metadata, OpFullType, OpId, Operation, Opout, Schema, Transition,
+    TransitionBundle, TypedAssigns,
 };
 
 pub const CONSIGNMENT_MAX_LIBS: usize = 1024;
@@ -48,6 +49,24 @@ pub enum OpRef<'op> {
     Transition(&'op Transition),
 }
 
+impl OpRef<'_> {
+    /// Returns a canonical encoding of the operation reference, suitable as a stable key for
+    /// external indexes.
+    ///
+    /// The encoding consists of a single byte tag for the operation kind (`0x00` for genesis,
+    /// `0x01` for state transitions) followed by the 32 bytes of the operation id.
+    pub fn to_canonical_bytes(&self) -> [u8; 33] {
+        let tag = match self {
+            Self::Genesis(_) => 0x00,
+            Self::Transition(_) => 0x01,
+        };
+        let mut bytes = [0u8; 33];
+        bytes[0] = tag;
+        bytes[1..].copy_from_slice(&self.id().to_byte_array());
+        bytes
+    }
+}
+
 impl<'op> Operation for OpRef<'op> {
     fn full_type(&self) -> OpFullType {
         match self {
@@ -108,8 +127,81 @@ impl<'op> Operation for OpRef<'op> {
 
 pub struct CheckedConsignment<'consignment, C: ConsignmentApi>(&'consignment C);
 
+/// Error building the provenance of an allocation.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum ProvenanceError {
+    /// operation {0} is not part of the consignment.
+    UnknownOperation(OpId),
+    /// operation {0} doesn't produce output {1}.
+    UnknownOpout(OpId, Opout),
+}
+
+/// Operation in the provenance of an allocation.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ProvenanceNode {
+    /// Witness transaction of the operation, `None` for genesis.
+    pub witness_id: Option<Txid>,
+    /// Operations producing the outputs spent by this operation.
+    pub parents: BTreeSet<OpId>,
+}
+
+/// Operations an allocation descends from, back to genesis.
+///
+/// Operations shared by multiple branches of the history are listed once.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ProvenanceTree {
+    /// Operation producing the allocation.
+    pub root: OpId,
+    /// Operations in the provenance, including the root and genesis.
+    pub nodes: BTreeMap<OpId, ProvenanceNode>,
+}
+
 impl<'consignment, C: ConsignmentApi> CheckedConsignment<'consignment, C> {
     pub fn new(consignment: &'consignment C) -> Self { Self(consignment) }
+
+    /// Walks back the operations spent to produce the given allocation, up to genesis.
+    pub fn provenance(&self, opout: Opout) -> Result<ProvenanceTree, ProvenanceError> {
+        let genesis = self.genesis();
+        let mut operations = BTreeMap::<OpId, (OpRef, Option<Txid>)>::new();
+        operations.insert(genesis.id(), (OpRef::Genesis(genesis), None));
+        for (bundle, _, witness_id) in self.bundles_info() {
+            for KnownTransition { opid, transition } in &bundle.known_transitions {
+                operations.insert(*opid, (OpRef::Transition(transition), Some(witness_id)));
+            }
+        }
+
+        let mut nodes = BTreeMap::new();
+        let mut queue = vec![opout];
+        while let Some(opout) = queue.pop() {
+            let (op, witness_id) = operations
+                .get(&opout.op)
+                .ok_or(ProvenanceError::UnknownOperation(opout.op))?;
+            if op
+                .assignments_by_type(opout.ty)
+                .is_none_or(|assigns| opout.no >= assigns.len_u16())
+            {
+                return Err(ProvenanceError::UnknownOpout(opout.op, opout));
+            }
+            if nodes.contains_key(&opout.op) {
+                continue;
+            }
+            let inputs = match op {
+                OpRef::Genesis(_) => vec![],
+                OpRef::Transition(transition) => transition.inputs.iter().collect(),
+            };
+            let parents = inputs.iter().map(|input| input.op).collect();
+            queue.extend(inputs);
+            nodes.insert(opout.op, ProvenanceNode {
+                witness_id: *witness_id,
+                parents,
+            });
+        }
+        Ok(ProvenanceTree {
+            root: opout.op,
+            nodes,
+        })
+    }
 }
 
 impl<C: ConsignmentApi> ConsignmentApi for CheckedConsignment<'_, C> {
@@ -157,4 +249,19 @@ pub trait ConsignmentApi {
             .collect::<BTreeSet<_>>()
             .into_iter()
     }
+
+    /// Returns ids of all the witness transactions the consignment depends on.
+    fn witness_ids(&self) -> BTreeSet<Txid> {
+        self.bundles_info()
+            .map(|(_, _, witness_id)| witness_id)
+            .collect()
+    }
+
+    /// Returns ids of all the bundles anchored to the given witness transaction.
+    fn bundles_for_witness(&self, witness_id: Txid) -> Vec<BundleId> {
+        self.bundles_info()
+            .filter(|(_, _, id)| *id == witness_id)
+            .map(|(b, _, _)| b.bundle_id())
+            .collect()
+    }
 }
@@ -0,0 +1,157 @@
+// RGB Consensus Library: consensus layer for RGB smart contracts.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright (C) 2025 RGB-Tools developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Deterministic witness resolver for tests.
+
+use amplify::confinement::LargeOrdMap;
+use bitcoin::{Transaction as Tx, Txid};
+use strict_encoding::{StrictDeserialize, StrictSerialize};
+
+use super::{
+    ConsignmentApi, ResolveWitness, WitnessOrdProvider, WitnessResolverError, WitnessStatus,
+};
+use crate::vm::WitnessOrd;
+use crate::ChainNet;
+
+/// Strict type library name for the test fixtures, kept apart from the consensus libraries.
+pub const LIB_NAME_RGB_FIXTURES: &str = "RGBFixtures";
+
+/// Witness transaction with its ordering information, as stored in a [`FixtureResolver`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_FIXTURES)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct WitnessFixture {
+    pub tx: Tx,
+    pub ord: WitnessOrd,
+}
+
+/// Witness resolver serving canned data, to be used in tests.
+///
+/// Fixtures can be stored and loaded with the [`StrictSerialize`] and [`StrictDeserialize`] APIs
+/// (or with serde, when the feature is enabled). A fixture for a regression snapshot can be
+/// created from a real consignment with [`FixtureResolver::record`].
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_RGB_FIXTURES)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct FixtureResolver {
+    pub chain_net: ChainNet,
+    pub witnesses: LargeOrdMap<Txid, WitnessFixture>,
+}
+
+impl StrictSerialize for FixtureResolver {}
+impl StrictDeserialize for FixtureResolver {}
+
+impl FixtureResolver {
+    pub fn new(chain_net: ChainNet) -> Self {
+        Self {
+            chain_net,
+            witnesses: none!(),
+        }
+    }
+
+    /// Adds a witness transaction to the fixture, replacing any previous entry.
+    pub fn insert(&mut self, tx: Tx, ord: WitnessOrd) {
+        self.witnesses
+            .insert(tx.compute_txid(), WitnessFixture { tx, ord })
+            .expect("fixture can't exceed u32::MAX witnesses");
+    }
+
+    /// Builds a fixture containing all the witnesses of the given consignment, as returned by
+    /// the provided resolver. Witnesses unknown to the resolver are not added to the fixture.
+    pub fn record(
+        consignment: &impl ConsignmentApi,
+        resolver: &impl ResolveWitness,
+        chain_net: ChainNet,
+    ) -> Result<Self, WitnessResolverError> {
+        resolver.check_chain_net(chain_net)?;
+        let mut fixture = Self::new(chain_net);
+        for (_, _, witness_id) in consignment.bundles_info() {
+            if let WitnessStatus::Resolved(tx, ord) = resolver.resolve_witness(witness_id)? {
+                fixture.insert(tx, ord);
+            }
+        }
+        Ok(fixture)
+    }
+}
+
+impl ResolveWitness for FixtureResolver {
+    fn resolve_witness(&self, witness_id: Txid) -> Result<WitnessStatus, WitnessResolverError> {
+        Ok(match self.witnesses.get(&witness_id) {
+            Some(WitnessFixture { tx, ord }) => WitnessStatus::Resolved(tx.clone(), *ord),
+            None => WitnessStatus::Unresolved,
+        })
+    }
+
+    fn check_chain_net(&self, chain_net: ChainNet) -> Result<(), WitnessResolverError> {
+        if self.chain_net != chain_net {
+            return Err(WitnessResolverError::WrongChainNet);
+        }
+        Ok(())
+    }
+}
+
+impl WitnessOrdProvider for FixtureResolver {
+    fn witness_ord(&self, witness_id: Txid) -> Result<WitnessOrd, WitnessResolverError> {
+        Ok(self
+            .witnesses
+            .get(&witness_id)
+            .map(|fixture| fixture.ord)
+            .unwrap_or(WitnessOrd::Archived))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::U32;
+    use strict_encoding::StrictDumb;
+
+    use super::*;
+
+    #[test]
+    fn fixture_roundtrip() {
+        let tx = Tx::strict_dumb();
+        let txid = tx.compute_txid();
+        let mut fixture = FixtureResolver::new(ChainNet::BitcoinRegtest);
+        fixture.insert(tx.clone(), WitnessOrd::Tentative);
+
+        let data = fixture.to_strict_serialized::<U32>().unwrap();
+        let restored = FixtureResolver::from_strict_serialized::<U32>(data).unwrap();
+        assert_eq!(restored, fixture);
+
+        assert_eq!(
+            restored.resolve_witness(txid),
+            Ok(WitnessStatus::Resolved(tx, WitnessOrd::Tentative))
+        );
+        assert_eq!(restored.witness_ord(txid), Ok(WitnessOrd::Tentative));
+        assert!(restored.check_chain_net(ChainNet::BitcoinRegtest).is_ok());
+        assert_eq!(
+            restored.check_chain_net(ChainNet::BitcoinMainnet),
+            Err(WitnessResolverError::WrongChainNet)
+        );
+    }
+}
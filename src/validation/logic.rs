@@ -30,19 +30,57 @@ use aluvm::reg::{Reg32, RegA};
 use aluvm::Vm;
 use amplify::confinement::Confined;
 use amplify::Wrapper;
+use bitcoin::hashes::Hash;
+use bitcoin::Txid;
 use strict_types::TypeSystem;
 
 use super::validator::ValidationError;
-use super::Failure;
+use super::{Failure, Status};
 use crate::schema::{AssignmentsSchema, GlobalSchema};
-use crate::validation::Scripts;
-use crate::vm::{ContractStateAccess, ContractStateEvolve, OpInfo, OrdOpRef, RgbIsa, VmContext};
+use crate::validation::{OpRef, Scripts};
+use crate::vm::{
+    ContractStateAccess, ContractStateEvolve, OpInfo, OrdOpRef, RgbIsa, VmContext, WitnessOrd,
+};
 use crate::{
-    Assign, AssignmentType, Assignments, AssignmentsRef, ExposedSeal, ExposedState, Genesis,
-    GlobalState, GlobalStateSchema, GlobalValues, MetaSchema, Metadata, OpId, Operation,
-    OwnedStateSchema, RevealedState, Schema, SealClosingStrategy, Transition, TypedAssigns,
+    Assign, AssignmentType, Assignments, AssignmentsRef, BundleId, ExposedSeal, ExposedState,
+    Genesis, GlobalState, GlobalStateSchema, GlobalValues, GraphSeal, MetaSchema, Metadata, OpId,
+    Operation, OwnedStateSchema, RevealedState, Schema, SealClosingStrategy, Transition,
+    TypedAssigns,
 };
 
+/// Returns the revealed state of each assignment, in the assignment order.
+fn revealed_states<Seal: ExposedSeal>(assigns: &TypedAssigns<Seal>) -> Vec<RevealedState> {
+    match assigns {
+        TypedAssigns::Declarative(set) => set
+            .iter()
+            .map(|a| a.as_revealed_state().state_data())
+            .collect(),
+        TypedAssigns::Fungible(set) => set
+            .iter()
+            .map(|a| a.as_revealed_state().state_data())
+            .collect(),
+        TypedAssigns::Structured(set) => set
+            .iter()
+            .map(|a| a.as_revealed_state().state_data())
+            .collect(),
+    }
+}
+
+/// Returns the revealed state of the assignment at the given index, if any.
+fn revealed_state_at<Seal: ExposedSeal>(
+    assigns: &TypedAssigns<Seal>,
+    no: u16,
+) -> Option<RevealedState> {
+    let index = no as usize;
+    match assigns {
+        TypedAssigns::Declarative(set) => {
+            set.get(index).map(|a| a.as_revealed_state().state_data())
+        }
+        TypedAssigns::Fungible(set) => set.get(index).map(|a| a.as_revealed_state().state_data()),
+        TypedAssigns::Structured(set) => set.get(index).map(|a| a.as_revealed_state().state_data()),
+    }
+}
+
 impl Schema {
     pub fn validate_state<'validator, S: ContractStateAccess + ContractStateEvolve>(
         &'validator self,
@@ -155,12 +193,90 @@ impl Schema {
             }
         }
         let contract_state = context.contract_state;
-        if contract_state.borrow_mut().evolve_state(op).is_err() {
-            return Err(ValidationError::InvalidConsignment(Failure::ContractStateFilled(opid)));
+        if let Err(err) = contract_state.borrow_mut().evolve_state(op) {
+            return Err(ValidationError::InvalidConsignment(Failure::ContractStateError(
+                opid,
+                err.to_string(),
+            )));
         }
         Ok(())
     }
 
+    /// Validates a single operation against the state it spends, without requiring a
+    /// consignment.
+    ///
+    /// Unlike [`Schema::validate_state`], the previous state is provided as the assignments
+    /// spent by the operation.
+    pub fn validate_operation_standalone<S: ContractStateAccess + ContractStateEvolve>(
+        &self,
+        types: &TypeSystem,
+        scripts: &Scripts,
+        genesis: &Genesis,
+        op: OrdOpRef,
+        contract_state: Rc<RefCell<S>>,
+        prev_state: &Assignments<GraphSeal>,
+    ) -> Result<(), ValidationError> {
+        let mut state_by_type = BTreeMap::<AssignmentType, Vec<RevealedState>>::new();
+        for (ty, assigns) in prev_state.iter() {
+            state_by_type
+                .entry(*ty)
+                .or_default()
+                .extend(revealed_states(assigns));
+        }
+        self.validate_state(types, scripts, genesis, op, contract_state, &state_by_type)
+    }
+
+    /// Checks a transition against the operations whose outputs it spends, before it gets
+    /// bundled and committed into a witness transaction.
+    ///
+    /// The transition is validated as a tentative operation of a yet unknown witness and bundle.
+    /// The returned status lists the spent opouts in [`Status::spent_opouts`].
+    ///
+    /// NB: like any validated operation, the transition evolves the provided `contract_state`,
+    /// here with a zero witness id and bundle id as placeholders. Callers must pass a scratch copy
+    /// of their state and discard it afterwards.
+    pub fn precheck_transition<S: ContractStateAccess + ContractStateEvolve>(
+        &self,
+        types: &TypeSystem,
+        scripts: &Scripts,
+        genesis: &Genesis,
+        transition: &Transition,
+        prev_ops: &[OpRef],
+        contract_state: Rc<RefCell<S>>,
+    ) -> Result<Status, ValidationError> {
+        let opid = transition.id();
+        if transition.contract_id != genesis.contract_id() {
+            return Err(ValidationError::InvalidConsignment(Failure::ContractMismatch(
+                opid,
+                transition.contract_id,
+            )));
+        }
+        let prev_ops = prev_ops
+            .iter()
+            .map(|op| (op.id(), op))
+            .collect::<BTreeMap<_, _>>();
+        let mut state_by_type = BTreeMap::<AssignmentType, Vec<RevealedState>>::new();
+        for input in &transition.inputs {
+            let state = prev_ops
+                .get(&input.op)
+                .and_then(|op| op.assignments_by_type(input.ty))
+                .and_then(|assigns| revealed_state_at(&assigns, input.no))
+                .ok_or(ValidationError::InvalidConsignment(Failure::NoPrevState(opid, input)))?;
+            state_by_type.entry(input.ty).or_default().push(state);
+        }
+        let op = OrdOpRef::Transition(
+            transition,
+            Txid::all_zeros(),
+            WitnessOrd::Tentative,
+            BundleId::from([0u8; 32]),
+        );
+        self.validate_state(types, scripts, genesis, op, contract_state, &state_by_type)?;
+        Ok(Status {
+            spent_opouts: transition.inputs.iter().copied().collect(),
+            ..default!()
+        })
+    }
+
     fn validate_metadata(
         &self,
         opid: OpId,
@@ -192,12 +308,11 @@ impl Schema {
                 )
                 .sem_id;
 
-            if types
-                .strict_deserialize_type(sem_id, value.as_ref())
-                .is_err()
-            {
+            if let Err(err) = types.strict_deserialize_type(sem_id, value.as_ref()) {
                 return Err(ValidationError::InvalidConsignment(Failure::SchemaInvalidMetadata(
-                    opid, sem_id,
+                    opid,
+                    sem_id,
+                    err.to_string(),
                 )));
             };
         }
@@ -239,6 +354,11 @@ impl Schema {
 
             // Checking number of field occurrences
             let count = set.len() as u16;
+            if count > 0 && (occ.max_value() == 0 || max_items.to_u32() == 0) {
+                return Err(ValidationError::InvalidConsignment(Failure::GlobalStateForbidden(
+                    opid, *type_id,
+                )));
+            }
             if let Err(err) = occ.check(count) {
                 return Err(ValidationError::InvalidConsignment(
                     Failure::SchemaGlobalStateOccurrences(opid, *type_id, err),
@@ -252,12 +372,9 @@ impl Schema {
 
             // Validating data types
             for data in set {
-                if types
-                    .strict_deserialize_type(sem_id, data.as_ref())
-                    .is_err()
-                {
+                if let Err(err) = types.strict_deserialize_type(sem_id, data.as_ref()) {
                     return Err(ValidationError::InvalidConsignment(
-                        Failure::SchemaInvalidGlobalValue(opid, *type_id, sem_id),
+                        Failure::SchemaInvalidGlobalValue(opid, *type_id, sem_id, err.to_string()),
                     ));
                 };
             }
@@ -371,12 +488,16 @@ impl OwnedStateSchema {
                     }
                     (OwnedStateSchema::Fungible(_), RevealedState::Fungible(_)) => {}
                     (OwnedStateSchema::Structured(sem_id), RevealedState::Structured(data)) => {
-                        if type_system
-                            .strict_deserialize_type(*sem_id, data.as_ref())
-                            .is_err()
+                        if let Err(err) =
+                            type_system.strict_deserialize_type(*sem_id, data.as_ref())
                         {
                             return Err(ValidationError::InvalidConsignment(
-                                Failure::SchemaInvalidOwnedValue(opid, state_type, *sem_id),
+                                Failure::SchemaInvalidOwnedValue(
+                                    opid,
+                                    state_type,
+                                    *sem_id,
+                                    err.to_string(),
+                                ),
                             ));
                         };
                     }
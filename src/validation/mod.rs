@@ -27,12 +27,25 @@ mod validator;
 mod consignment;
 mod status;
 mod commitments;
+mod recording;
+#[cfg(feature = "test-helpers")]
+mod fixture;
 
 pub use commitments::{DbcError, DbcProof, EAnchor};
-pub use consignment::{CheckedConsignment, ConsignmentApi, OpRef, Scripts, CONSIGNMENT_MAX_LIBS};
+pub use consignment::{
+    CheckedConsignment, ConsignmentApi, OpRef, ProvenanceError, ProvenanceNode, ProvenanceTree,
+    Scripts, CONSIGNMENT_MAX_LIBS,
+};
+#[cfg(feature = "test-helpers")]
+pub use fixture::{FixtureResolver, WitnessFixture, LIB_NAME_RGB_FIXTURES};
 pub use opouts_dag::{OpoutsDag, OpoutsDagData, OpoutsDagIndex, OpoutsDagInfo};
-pub use status::{Failure, Info, Status, UnsafeHistoryMap, Validity, Warning};
+pub use recording::{RecordingResolver, ReplayResolver, ResolverCall, ResolverTranscript};
+pub use status::{
+    ConsignmentItem, Failure, Info, PhaseTimings, ScriptType, Status, UnsafeHistory, Validity,
+    Warning,
+};
 pub use validator::{
-    ResolveWitness, ValidationConfig, ValidationError, Validator, WitnessOrdProvider,
-    WitnessResolverError, WitnessStatus,
+    compute_state, validate_many, validate_single_seal, CachingResolver, ResolveWitness,
+    ValidationConfig, ValidationError, Validator, WitnessOrdProvider, WitnessResolverError,
+    WitnessStatus,
 };
@@ -16,7 +16,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use daggy::{Dag, NodeIndex};
 
@@ -26,7 +26,7 @@ use crate::{KnownTransition, OpId, Operation, Opout, Transition};
 pub type OpoutsDag = Dag<Opout, ()>;
 
 /// Index to store opout-node relations
-pub type OpoutsDagIndex = HashMap<Opout, NodeIndex>;
+pub type OpoutsDagIndex = BTreeMap<Opout, NodeIndex>;
 
 /// Operations DAG and related opout-node index
 pub type OpoutsDagData = (OpoutsDag, OpoutsDagIndex);
@@ -42,7 +42,7 @@ impl OpoutsDagInfo {
     pub fn new() -> Self {
         Self {
             dag: Dag::new(),
-            index: HashMap::new(),
+            index: BTreeMap::new(),
             cached_outputs: HashMap::new(),
         }
     }
@@ -0,0 +1,272 @@
+// RGB Consensus Library: consensus layer for RGB smart contracts.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Copyright (C) 2025 RGB-Tools developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Resolver decorators recording witness queries and replaying them offline.
+
+use std::cell::{Cell, RefCell};
+use std::num::NonZeroU32;
+
+use bitcoin::Txid;
+
+use super::{ResolveWitness, WitnessResolverError, WitnessStatus};
+use crate::ChainNet;
+
+/// Single resolver call, together with the result it produced.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub enum ResolverCall {
+    ResolveWitness {
+        witness_id: Txid,
+        result: Result<WitnessStatus, WitnessResolverError>,
+    },
+    ResolveWitnessAt {
+        witness_id: Txid,
+        at_height: NonZeroU32,
+        result: Result<WitnessStatus, WitnessResolverError>,
+    },
+    ResolveWitnesses {
+        witness_ids: Vec<Txid>,
+        result: Result<Vec<WitnessStatus>, WitnessResolverError>,
+    },
+    CheckChainNet {
+        chain_net: ChainNet,
+        result: Result<(), WitnessResolverError>,
+    },
+}
+
+/// Ordered list of the calls performed on a resolver during a validation session.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct ResolverTranscript {
+    pub calls: Vec<ResolverCall>,
+}
+
+/// Resolver wrapper recording every call and its result into a [`ResolverTranscript`].
+pub struct RecordingResolver<R: ResolveWitness> {
+    inner: R,
+    transcript: RefCell<ResolverTranscript>,
+}
+
+impl<R: ResolveWitness> RecordingResolver<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            transcript: none!(),
+        }
+    }
+
+    /// Returns the calls recorded so far.
+    pub fn transcript(&self) -> ResolverTranscript { self.transcript.borrow().clone() }
+
+    /// Releases the wrapped resolver together with the recorded transcript.
+    pub fn into_parts(self) -> (R, ResolverTranscript) {
+        (self.inner, self.transcript.into_inner())
+    }
+}
+
+impl<R: ResolveWitness> ResolveWitness for RecordingResolver<R> {
+    fn resolve_witness(&self, witness_id: Txid) -> Result<WitnessStatus, WitnessResolverError> {
+        let result = self.inner.resolve_witness(witness_id);
+        self.transcript
+            .borrow_mut()
+            .calls
+            .push(ResolverCall::ResolveWitness {
+                witness_id,
+                result: result.clone(),
+            });
+        result
+    }
+
+    fn resolve_witness_at(
+        &self,
+        witness_id: Txid,
+        at_height: NonZeroU32,
+    ) -> Result<WitnessStatus, WitnessResolverError> {
+        let result = self.inner.resolve_witness_at(witness_id, at_height);
+        self.transcript
+            .borrow_mut()
+            .calls
+            .push(ResolverCall::ResolveWitnessAt {
+                witness_id,
+                at_height,
+                result: result.clone(),
+            });
+        result
+    }
+
+    fn resolve_witnesses(
+        &self,
+        witness_ids: &[Txid],
+    ) -> Result<Vec<WitnessStatus>, WitnessResolverError> {
+        let result = self.inner.resolve_witnesses(witness_ids);
+        self.transcript
+            .borrow_mut()
+            .calls
+            .push(ResolverCall::ResolveWitnesses {
+                witness_ids: witness_ids.to_vec(),
+                result: result.clone(),
+            });
+        result
+    }
+
+    fn check_chain_net(&self, chain_net: ChainNet) -> Result<(), WitnessResolverError> {
+        let result = self.inner.check_chain_net(chain_net);
+        self.transcript
+            .borrow_mut()
+            .calls
+            .push(ResolverCall::CheckChainNet {
+                chain_net,
+                result: result.clone(),
+            });
+        result
+    }
+}
+
+/// Resolver serving the results stored in a [`ResolverTranscript`].
+///
+/// Calls must be performed in the same order they were recorded; a call not matching the next
+/// transcript entry (or performed once the transcript is exhausted) fails with
+/// [`WitnessResolverError::ResolverIssue`].
+pub struct ReplayResolver {
+    transcript: ResolverTranscript,
+    cursor: Cell<usize>,
+}
+
+impl ReplayResolver {
+    pub fn new(transcript: ResolverTranscript) -> Self {
+        Self {
+            transcript,
+            cursor: Cell::new(0),
+        }
+    }
+
+    /// Whether all the recorded calls have been replayed.
+    pub fn is_exhausted(&self) -> bool { self.cursor.get() >= self.transcript.calls.len() }
+
+    fn next_call(&self) -> Option<&ResolverCall> {
+        let call = self.transcript.calls.get(self.cursor.get())?;
+        self.cursor.set(self.cursor.get() + 1);
+        Some(call)
+    }
+}
+
+impl ResolveWitness for ReplayResolver {
+    fn resolve_witness(&self, witness_id: Txid) -> Result<WitnessStatus, WitnessResolverError> {
+        match self.next_call() {
+            Some(ResolverCall::ResolveWitness {
+                witness_id: recorded,
+                result,
+            }) if *recorded == witness_id => result.clone(),
+            _ => Err(WitnessResolverError::ResolverIssue(
+                Some(witness_id),
+                s!("witness query doesn't match the recorded transcript"),
+            )),
+        }
+    }
+
+    fn resolve_witness_at(
+        &self,
+        witness_id: Txid,
+        at_height: NonZeroU32,
+    ) -> Result<WitnessStatus, WitnessResolverError> {
+        match self.next_call() {
+            Some(ResolverCall::ResolveWitnessAt {
+                witness_id: recorded,
+                at_height: recorded_height,
+                result,
+            }) if *recorded == witness_id && *recorded_height == at_height => result.clone(),
+            _ => Err(WitnessResolverError::ResolverIssue(
+                Some(witness_id),
+                s!("height-pinned witness query doesn't match the recorded transcript"),
+            )),
+        }
+    }
+
+    fn resolve_witnesses(
+        &self,
+        witness_ids: &[Txid],
+    ) -> Result<Vec<WitnessStatus>, WitnessResolverError> {
+        match self.next_call() {
+            Some(ResolverCall::ResolveWitnesses {
+                witness_ids: recorded,
+                result,
+            }) if recorded == witness_ids => result.clone(),
+            _ => Err(WitnessResolverError::ResolverIssue(
+                None,
+                s!("batch witness query doesn't match the recorded transcript"),
+            )),
+        }
+    }
+
+    fn check_chain_net(&self, chain_net: ChainNet) -> Result<(), WitnessResolverError> {
+        match self.next_call() {
+            Some(ResolverCall::CheckChainNet {
+                chain_net: recorded,
+                result,
+            }) if *recorded == chain_net => result.clone(),
+            _ => Err(WitnessResolverError::ResolverIssue(
+                None,
+                s!("chain-network check doesn't match the recorded transcript"),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::hashes::Hash;
+
+    use super::*;
+
+    struct EmptyResolver;
+
+    impl ResolveWitness for EmptyResolver {
+        fn resolve_witness(&self, _: Txid) -> Result<WitnessStatus, WitnessResolverError> {
+            Ok(WitnessStatus::Unresolved)
+        }
+
+        fn check_chain_net(&self, _: ChainNet) -> Result<(), WitnessResolverError> { Ok(()) }
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let txid = Txid::all_zeros();
+        let recorder = RecordingResolver::new(EmptyResolver);
+        recorder.check_chain_net(ChainNet::BitcoinRegtest).unwrap();
+        recorder.resolve_witness(txid).unwrap();
+        let (_, transcript) = recorder.into_parts();
+        assert_eq!(transcript.calls.len(), 2);
+
+        let replay = ReplayResolver::new(transcript.clone());
+        assert_eq!(replay.check_chain_net(ChainNet::BitcoinRegtest), Ok(()));
+        assert_eq!(replay.resolve_witness(txid), Ok(WitnessStatus::Unresolved));
+        assert!(replay.is_exhausted());
+        assert!(replay.resolve_witness(txid).is_err());
+
+        let replay = ReplayResolver::new(transcript);
+        assert!(replay.check_chain_net(ChainNet::BitcoinMainnet).is_err());
+    }
+}
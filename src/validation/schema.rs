@@ -20,10 +20,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeSet;
+
 use strict_types::TypeSystem;
 
 use super::validator::ValidationError;
-use crate::{validation, OpFullType, OpSchema, OwnedStateSchema, Schema};
+use crate::{
+    validation, AssignmentType, OpFullType, OpSchema, OwnedStateSchema, Schema, TransitionType,
+};
 
 impl Schema {
     pub fn verify(&self, types: &TypeSystem) -> Result<(), ValidationError> {
@@ -67,6 +71,33 @@ impl Schema {
         Ok(())
     }
 
+    /// Lists the owned state types which transitions take as inputs but which neither genesis nor
+    /// any transition can ever assign, making those transitions impossible to perform.
+    pub fn unsatisfiable_inputs(&self) -> Vec<(TransitionType, AssignmentType)> {
+        let produced = self
+            .genesis
+            .assignments
+            .keys()
+            .chain(
+                self.transitions
+                    .values()
+                    .flat_map(|details| details.transition_schema.assignments.keys()),
+            )
+            .copied()
+            .collect::<BTreeSet<_>>();
+        self.transitions
+            .iter()
+            .flat_map(|(transition_type, details)| {
+                details
+                    .transition_schema
+                    .inputs
+                    .keys()
+                    .filter(|ty| !produced.contains(*ty))
+                    .map(|ty| (*transition_type, *ty))
+            })
+            .collect()
+    }
+
     fn verify_operation(
         &self,
         op_type: OpFullType,
@@ -84,11 +115,16 @@ impl Schema {
                 validation::Failure::SchemaOpEmptyInputs(op_type),
             ));
         }
-        for type_id in schema.globals().keys() {
-            if !self.global_types.contains_key(type_id) {
+        for (type_id, occ) in schema.globals() {
+            let Some(global_details) = self.global_types.get(type_id) else {
                 return Err(ValidationError::InvalidConsignment(
                     validation::Failure::SchemaOpGlobalTypeUnknown(op_type, *type_id),
                 ));
+            };
+            if occ.max_value() as u32 > global_details.global_state_schema.max_items.to_u32() {
+                return Err(ValidationError::InvalidConsignment(
+                    validation::Failure::SchemaInconsistentGlobalBounds(op_type, *type_id),
+                ));
             }
         }
         for type_id in schema.assignments().keys() {
@@ -21,12 +21,13 @@
 // limitations under the License.
 
 use core::ops::AddAssign;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
 
 use aluvm::library::LibId;
 use amplify::num::u24;
-use bitcoin::{OutPoint, Txid};
+use bitcoin::{Amount, FeeRate, OutPoint, Script, Txid};
 use strict_types::{SemId, Ty};
 
 use crate::commit_verify::mpc::InvalidProof;
@@ -35,11 +36,29 @@ use crate::seals::txout::CloseMethod;
 use crate::validation::OpoutsDagData;
 use crate::vm::WitnessOrd;
 use crate::{
-    BundleId, ChainNet, ContractId, OccurrencesMismatch, OpFullType, OpId, Opout,
+    BundleId, ChainNet, ContractId, OccurrencesMismatch, OpFullType, OpId, Opout, OutputSeal,
     SealClosingStrategy, StateType,
 };
 
-pub type UnsafeHistoryMap = HashMap<u32, HashSet<Txid>>;
+/// Witness transactions of the validated history which can't be considered safe yet.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct UnsafeHistory {
+    /// Witnesses not mined yet.
+    pub unconfirmed: BTreeSet<Txid>,
+    /// Witnesses mined above the safe height, by block height.
+    pub above_safe_height: BTreeMap<u32, BTreeSet<Txid>>,
+}
+
+impl UnsafeHistory {
+    pub fn is_empty(&self) -> bool {
+        self.unconfirmed.is_empty() && self.above_safe_height.is_empty()
+    }
+}
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Display)]
 #[repr(u8)]
@@ -49,6 +68,9 @@ pub enum Validity {
 
     #[display("valid, with warnings")]
     Warnings,
+
+    #[display("valid, but relying on unsafe history")]
+    ValidButUnsafe,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -60,8 +82,40 @@ pub enum Validity {
 pub struct Status {
     pub warnings: Vec<Warning>,
     pub info: Vec<Info>,
-    pub tx_ord_map: HashMap<Txid, WitnessOrd>,
+    pub tx_ord_map: BTreeMap<Txid, WitnessOrd>,
+    /// Seals found closed by each witness transaction.
+    pub seals_by_witness: BTreeMap<Txid, Vec<OutputSeal>>,
+    /// Opouts spent by the validated operations.
+    pub spent_opouts: BTreeSet<Opout>,
     pub dag_data_opt: Option<OpoutsDagData>,
+    /// Time spent in each validation phase, if requested.
+    pub timings: Option<PhaseTimings>,
+}
+
+/// Time spent in each phase of the validation.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct PhaseTimings {
+    /// Consignment integrity pre-checks.
+    pub integrity: Duration,
+    /// Schema and type system validation.
+    pub schema: Duration,
+    /// Genesis validation.
+    pub genesis: Duration,
+    /// Batch resolution of the witnesses.
+    pub witnesses: Duration,
+    /// Seal closing and state validation of all the bundles.
+    pub bundles: Duration,
+}
+
+impl PhaseTimings {
+    pub fn total(&self) -> Duration {
+        self.integrity + self.schema + self.genesis + self.witnesses + self.bundles
+    }
 }
 
 impl Display for Status {
@@ -92,6 +146,10 @@ impl AddAssign for Status {
     fn add_assign(&mut self, rhs: Self) {
         self.warnings.extend(rhs.warnings);
         self.info.extend(rhs.info);
+        self.spent_opouts.extend(rhs.spent_opouts);
+        for (txid, seals) in rhs.seals_by_witness {
+            self.seals_by_witness.entry(txid).or_default().extend(seals);
+        }
     }
 }
 
@@ -108,11 +166,94 @@ impl Status {
         self
     }
 
+    /// Number of confirmations at which [`Status::confidence`] reaches its maximum.
+    pub const FULL_CONFIDENCE_DEPTH: u32 = 6;
+
+    /// Returns a 0-100 score of how deeply buried the validated history is, given the current
+    /// blockchain tip height.
+    ///
+    /// The score grows linearly with the number of confirmations of the least confirmed witness,
+    /// reaching 100 at [`Self::FULL_CONFIDENCE_DEPTH`] confirmations. It is 0 if any witness is
+    /// not mined (or is mined above the tip), and 100 if the history has no witnesses at all.
+    pub fn confidence(&self, tip_height: u32) -> u8 {
+        let mut min_depth = Self::FULL_CONFIDENCE_DEPTH;
+        for ord in self.tx_ord_map.values() {
+            let WitnessOrd::Mined(pos) = ord else {
+                return 0;
+            };
+            let depth = tip_height
+                .saturating_add(1)
+                .saturating_sub(pos.height().get());
+            min_depth = min_depth.min(depth);
+        }
+        (min_depth * 100 / Self::FULL_CONFIDENCE_DEPTH) as u8
+    }
+
     pub fn validity(&self) -> Validity {
-        if !self.warnings.is_empty() {
+        if self.warnings.is_empty() {
+            Validity::Valid
+        } else if self
+            .warnings
+            .iter()
+            .all(|warning| matches!(warning, Warning::UnsafeHistory(_)))
+        {
+            Validity::ValidButUnsafe
+        } else {
             Validity::Warnings
+        }
+    }
+}
+
+/// Kind of consignment items which amount can be limited during validation.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+#[display(lowercase)]
+pub enum ConsignmentItem {
+    Bundles,
+    Operations,
+}
+
+/// Type of a transaction output script, as reported in failures.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+#[display(lowercase)]
+pub enum ScriptType {
+    P2pk,
+    P2pkh,
+    P2sh,
+    P2wpkh,
+    P2wsh,
+    P2tr,
+    OpReturn,
+    Other,
+}
+
+impl From<&Script> for ScriptType {
+    fn from(script: &Script) -> Self {
+        if script.is_p2pk() {
+            ScriptType::P2pk
+        } else if script.is_p2pkh() {
+            ScriptType::P2pkh
+        } else if script.is_p2sh() {
+            ScriptType::P2sh
+        } else if script.is_p2wpkh() {
+            ScriptType::P2wpkh
+        } else if script.is_p2wsh() {
+            ScriptType::P2wsh
+        } else if script.is_p2tr() {
+            ScriptType::P2tr
+        } else if script.is_op_return() {
+            ScriptType::OpReturn
         } else {
-            Validity::Valid
+            ScriptType::Other
         }
     }
 }
@@ -133,6 +274,16 @@ pub enum Failure {
     /// configuration).
     ResolverChainNetMismatch(ChainNet),
 
+    /// the resolver stopped matching the chain-network pair (validator runs in chain_net={0}
+    /// configuration) after witnesses were resolved.
+    ResolverChainNetChanged(ChainNet),
+
+    /// consignment is for contract {actual} while contract {expected} was expected.
+    UnexpectedContract {
+        expected: ContractId,
+        actual: ContractId,
+    },
+
     /// schema {actual} provided for the consignment validation doesn't match
     /// schema {expected} used by the contract. This means that the consignment
     /// is invalid.
@@ -145,6 +296,8 @@ pub enum Failure {
 
     /// type with sem_id {0} does not match the trusted one {1:?} (found {2})
     TypeSystemMismatch(SemId, Box<Option<Ty<SemId>>>, Box<Ty<SemId>>),
+    /// consignment type system references type with sem_id {0} which it doesn't contain.
+    IncompleteTypeSystem(SemId),
     /// schema global state #{0} uses semantic data type absent in type library
     /// ({1}).
     SchemaGlobalSemIdUnknown(schema::GlobalStateType, SemId),
@@ -161,6 +314,9 @@ pub enum Failure {
     SchemaOpMetaTypeUnknown(OpFullType, schema::MetaType),
     /// schema for {0} references undeclared global state type {1}.
     SchemaOpGlobalTypeUnknown(OpFullType, schema::GlobalStateType),
+    /// schema for {0} allows more occurrences of global state type {1} than the maximum number
+    /// of items declared for it.
+    SchemaInconsistentGlobalBounds(OpFullType, schema::GlobalStateType),
     /// schema for {0} references undeclared owned state type {1}.
     SchemaOpAssignmentTypeUnknown(OpFullType, schema::AssignmentType),
 
@@ -181,16 +337,35 @@ pub enum Failure {
     /// number of global state entries of type {1} in operation {0} exceeds
     /// schema-defined maximum for that global state type ({2} vs {3}).
     SchemaGlobalStateLimit(OpId, schema::GlobalStateType, u16, u24),
+    /// operation {0} contains global state of type {1}, for which the schema allows no entries.
+    GlobalStateForbidden(OpId, schema::GlobalStateType),
     /// required metadata type {1} is not present in the operation {0}.
     SchemaNoMetadata(OpId, schema::MetaType),
     /// invalid metadata in operation {0} not matching semantic type id {1}.
-    SchemaInvalidMetadata(OpId, SemId),
+    /// Details: {2}
+    SchemaInvalidMetadata(OpId, SemId, String),
     /// invalid global state value in operation {0}, state type #{1} which does
-    /// not match semantic type id {2}.
-    SchemaInvalidGlobalValue(OpId, schema::GlobalStateType, SemId),
+    /// not match semantic type id {2}. Details: {3}
+    SchemaInvalidGlobalValue(OpId, schema::GlobalStateType, SemId, String),
+    /// metadata of type {meta_type} in operation {opid} is {size} bytes long, exceeding the
+    /// limit of {limit} bytes.
+    MetadataTooLarge {
+        opid: OpId,
+        meta_type: schema::MetaType,
+        size: usize,
+        limit: usize,
+    },
+    /// global state value of type {global_type} in operation {opid} is {size} bytes long,
+    /// exceeding the limit of {limit} bytes.
+    GlobalValueTooLarge {
+        opid: OpId,
+        global_type: schema::GlobalStateType,
+        size: usize,
+        limit: usize,
+    },
     /// invalid owned state value in operation {0}, state type #{1} which does
-    /// not match semantic type id {2}.
-    SchemaInvalidOwnedValue(OpId, schema::AssignmentType, SemId),
+    /// not match semantic type id {2}. Details: {3}
+    SchemaInvalidOwnedValue(OpId, schema::AssignmentType, SemId, String),
     /// invalid number of input entries of type {1} in operation {0} - {2}
     SchemaInputOccurrences(OpId, schema::AssignmentType, OccurrencesMismatch),
     /// invalid number of assignment entries of type {1} in operation {0} - {2}
@@ -200,31 +375,58 @@ pub enum Failure {
     /// opout {0} is referenced within the history multiple times. RGB
     /// contracts allow only direct acyclic graphs.
     CyclicGraph(Opout),
+    // Reported only by transition pre-checks: consignment validation rejects transitions of other
+    // contracts with BundleContractMismatch before validating any of them
     /// operation {0} is under a different contract {1}.
     ContractMismatch(OpId, ContractId),
+    /// transition bundle {0} contains a transition of a different contract {1}.
+    BundleContractMismatch(BundleId, ContractId),
     /// transition claims ID {0} which differs from the actual one {1}
     TransitionIdMismatch(OpId, OpId),
+    /// consignment contains more than {limit} {kind} (at least {count} found).
+    ConsignmentTooLarge {
+        kind: ConsignmentItem,
+        count: usize,
+        limit: usize,
+    },
+
+    /// operation id {0} is used by more than one operation in the consignment.
+    DuplicateOpId(OpId),
 
     // Errors checking bundle commitments
+    /// transition bundle {bundle_id} input map has {map_len} entries, while its transitions
+    /// spend {inputs_len} inputs.
+    InputMapSizeMismatch {
+        bundle_id: BundleId,
+        map_len: usize,
+        inputs_len: usize,
+    },
+    /// transition bundle {0} is not present in the consignment.
+    BundleAbsent(BundleId),
     /// transition bundle {0} references non-existing input {1} in witness {2}.
     WitnessMissingInput(BundleId, OutPoint, Txid),
     /// transition bundle {0} input map does not include operation {1} as the one
     /// spending opout {1}.
     InputMapTransitionMismatch(BundleId, OpId, Opout),
+    /// transition bundle {0} input map assigns opout {2} to operation {1}, which doesn't spend
+    /// it.
+    InputMapMismatch(BundleId, OpId, Opout),
 
     // Errors checking seal closing
     /// transition {0} references previous state {1} that cannot be found.
     NoPrevState(OpId, Opout),
     /// bundle {0} public witness {1} is not known to the resolver.
     SealNoPubWitness(BundleId, Txid),
+    /// bundle {0} public witness {1} was previously reported as {2}, while it is now {3}.
+    WitnessOrdRegression(BundleId, Txid, WitnessOrd, WitnessOrd),
     /// transition bundle {0} doesn't close seal with the witness {1}. Details:
     /// {2}
     SealsInvalid(BundleId, Txid, String),
-    /// transition bundle {0} is not properly anchored to the witness {1}.
-    /// Details: {2}
+    /// MPC proof of transition bundle {0} anchored to the witness {1} doesn't place it at the
+    /// contract position. Details: {2}
     MpcInvalid(BundleId, Txid, Box<InvalidProof>),
-    /// witness transaction {0} has no taproot or OP_RETURN output.
-    NoDbcOutput(Txid),
+    /// witness transaction {0} has no taproot or OP_RETURN output (output types: {1:?}).
+    NoDbcOutput(Txid, Vec<ScriptType>),
     /// first DBC-compatible output of witness transaction {0} doesn't match the provided proof
     /// type ({1})
     InvalidProofType(Txid, CloseMethod),
@@ -246,15 +448,24 @@ pub enum Failure {
         expected: schema::FungibleType,
         found: schema::FungibleType,
     },
+    /// operation {0} assigns zero value to fungible state of type {1}.
+    ZeroValueAssignment(OpId, schema::AssignmentType),
     /// evaluation of AluVM script for operation {0} has failed with the code
     /// {1:?} and message {2:?}.
     ScriptFailure(OpId, Option<u8>, Option<String>),
-    /// contract state can't fit more data (at operation id {0}).
-    ContractStateFilled(OpId),
+    /// contract state can't be evolved with operation {0}: {1}
+    ContractStateError(OpId, String),
     /// operation {0} commits to a missing script {1}.
     MissingScript(OpId, LibId),
+    /// scripts {0:?} used as schema validators are missing from the consignment.
+    MissingScripts(BTreeSet<LibId>),
     /// operation {0} commits to a script which ID {1} doesn't match the actual one {2}.
     ScriptIDMismatch(OpId, LibId, LibId),
+    /// schema references script {0} which is not in the set of trusted libraries.
+    UntrustedScript(LibId),
+
+    /// validation warning treated as an error: {0}
+    WarningAsError(Warning),
 
     /// Custom error by external services on top of RGB Consensus.
     #[display(inner)]
@@ -270,7 +481,28 @@ pub enum Failure {
 #[display(doc_comments)]
 pub enum Warning {
     /// Map of transfer history TXs with potentially unsafe height.
-    UnsafeHistory(UnsafeHistoryMap),
+    UnsafeHistory(UnsafeHistory),
+
+    /// unconfirmed witness transaction {0} pays a fee rate of {1} sat/kwu, which is below the
+    /// required minimum.
+    LowFeeWitness(Txid, FeeRate),
+
+    /// script {0} is not used by any schema validator, directly or through its imports.
+    UnreachableScript(LibId),
+
+    /// script {0} imported by a schema validator script is missing from the consignment.
+    MissingImportedScript(LibId),
+
+    /// schema transition {0} takes inputs of owned state type {1}, which no operation can
+    /// assign.
+    UnsatisfiableInput(schema::TransitionType, schema::AssignmentType),
+
+    /// tapret commitment output of witness transaction {0} has a value of {1}, which is below
+    /// the dust limit.
+    DustCommitmentOutput(Txid, Amount),
+
+    /// witness transaction {0} was previously reported as {1}, while it is now {2}.
+    WitnessOrdRegression(Txid, WitnessOrd, WitnessOrd),
 
     /// Custom warning by external services on top of RGB Consensus.
     #[display(inner)]
@@ -288,4 +520,53 @@ pub enum Info {
     /// Custom info by external services on top of RGB Consensus.
     #[display(inner)]
     Custom(String),
+
+    /// consignment type system was trusted as provided, without third-party attestation.
+    SelfAttestedTypes,
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use bitcoin::hashes::Hash;
+
+    use super::*;
+    use crate::vm::WitnessPos;
+
+    fn status_with(ords: impl IntoIterator<Item = WitnessOrd>) -> Status {
+        let tx_ord_map = ords
+            .into_iter()
+            .enumerate()
+            .map(|(no, ord)| {
+                let mut txid = [0u8; 32];
+                txid[0] = no as u8;
+                (Txid::from_byte_array(txid), ord)
+            })
+            .collect();
+        Status {
+            tx_ord_map,
+            ..Status::new()
+        }
+    }
+
+    fn mined(height: u32) -> WitnessOrd {
+        WitnessOrd::Mined(
+            WitnessPos::bitcoin(NonZeroU32::new(height).unwrap(), 1_700_000_000).unwrap(),
+        )
+    }
+
+    #[test]
+    fn confidence() {
+        let tip = 800_000;
+        assert_eq!(status_with([]).confidence(tip), 100);
+        assert_eq!(status_with([WitnessOrd::Tentative]).confidence(tip), 0);
+        assert_eq!(status_with([mined(tip), WitnessOrd::Tentative]).confidence(tip), 0);
+        assert_eq!(status_with([mined(tip + 1)]).confidence(tip), 0);
+        assert_eq!(status_with([mined(tip)]).confidence(tip), 16);
+        let full = Status::FULL_CONFIDENCE_DEPTH;
+        assert_eq!(status_with([mined(tip + 1 - full)]).confidence(tip), 100);
+        assert_eq!(status_with([mined(tip - 100)]).confidence(tip), 100);
+        assert_eq!(status_with([mined(tip - 100), mined(tip - 2)]).confidence(tip), 50);
+    }
 }
@@ -22,29 +22,45 @@
 
 use std::cell::RefCell;
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
-use std::num::NonZeroU32;
+use std::num::{NonZeroU32, NonZeroUsize};
 use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use aluvm::library::LibId;
 use amplify::confinement::{Collection, ConfinedOrdMap};
-use bitcoin::{Transaction as Tx, Txid};
+use bitcoin::{Amount, FeeRate, Transaction as Tx, Txid};
 use strict_types::TypeSystem;
 
-use super::status::{Failure, Warning};
+use super::status::{
+    ConsignmentItem, Failure, Info, PhaseTimings, ScriptType, UnsafeHistory, Warning,
+};
 use super::{CheckedConsignment, ConsignmentApi, DbcProof, Status};
 use crate::assignments::RevealedAssign;
 use crate::commit_verify::mpc;
 use crate::dbc::{self, Anchor};
 use crate::operation::seal::ExposedSeal;
-use crate::seals::txout::{CloseMethod, Witness};
+use crate::seals::txout::{CloseMethod, TxoSeal, Witness};
 use crate::single_use_seals::SealWitness;
 use crate::txout::BlindSeal;
 use crate::validation::{OpoutsDagInfo, Scripts};
-use crate::vm::{ContractStateAccess, ContractStateEvolve, OrdOpRef, WitnessOrd};
+use crate::vm::{ContractStateAccess, ContractStateEvolve, OrdOpRef, WitnessOrd, WitnessPos};
 use crate::{
-    AssignmentType, Assignments, BundleId, ChainNet, ContractId, KnownTransition, OpId, Operation,
-    Opout, RevealedState, SchemaId, TransitionBundle,
+    AssignmentType, Assignments, BundleId, ChainNet, ContractId, KnownTransition, Layer1, OpId,
+    Operation, Opout, OutputSeal, RevealedState, SchemaId, TransitionBundle,
 };
 
+/// Emits a debug event about validation progress when the `tracing` feature is enabled.
+///
+/// Events are emitted under the library target, so they are enabled with
+/// `RUST_LOG=rgbcore=debug`.
+macro_rules! trace_validation {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
+
 /// Error validating a consignment.
 #[derive(Clone, PartialEq, Eq, Debug, Display, Error, From)]
 #[display(doc_comments)]
@@ -91,6 +107,38 @@ pub trait ResolveWitness {
     /// Provide the [`WitnessStatus`] for a TX with the given `witness_id`.
     fn resolve_witness(&self, witness_id: Txid) -> Result<WitnessStatus, WitnessResolverError>;
 
+    /// Provide the [`WitnessStatus`] for a TX with the given `witness_id` as it was at the given
+    /// blockchain height.
+    ///
+    /// The default implementation relies on [`Self::resolve_witness`] and reports witnesses mined
+    /// above `at_height` as [`WitnessOrd::Tentative`].
+    fn resolve_witness_at(
+        &self,
+        witness_id: Txid,
+        at_height: NonZeroU32,
+    ) -> Result<WitnessStatus, WitnessResolverError> {
+        Ok(match self.resolve_witness(witness_id)? {
+            WitnessStatus::Resolved(tx, WitnessOrd::Mined(pos)) if pos.height() > at_height => {
+                WitnessStatus::Resolved(tx, WitnessOrd::Tentative)
+            }
+            witness_status => witness_status,
+        })
+    }
+
+    /// Provide the [`WitnessStatus`] for each TX in `witness_ids`, in the same order.
+    ///
+    /// The default implementation resolves the witnesses one by one; resolvers backed by remote
+    /// services should override it to run the queries concurrently.
+    fn resolve_witnesses(
+        &self,
+        witness_ids: &[Txid],
+    ) -> Result<Vec<WitnessStatus>, WitnessResolverError> {
+        witness_ids
+            .iter()
+            .map(|witness_id| self.resolve_witness(*witness_id))
+            .collect()
+    }
+
     /// Check that the resolver works with the expected [`ChainNet`].
     fn check_chain_net(&self, chain_net: ChainNet) -> Result<(), WitnessResolverError>;
 }
@@ -111,6 +159,23 @@ pub enum WitnessStatus {
 }
 
 impl WitnessStatus {
+    /// Witness TX mined in a block at the given height and timestamp.
+    ///
+    /// Returns [`None`] if the timestamp precedes the genesis block of the `layer1` chain.
+    pub fn confirmed(tx: Tx, layer1: Layer1, height: NonZeroU32, timestamp: i64) -> Option<Self> {
+        let pos = match layer1 {
+            Layer1::Bitcoin => WitnessPos::bitcoin(height, timestamp),
+            Layer1::Liquid => WitnessPos::liquid(height, timestamp),
+        }?;
+        Some(Self::Resolved(tx, WitnessOrd::Mined(pos)))
+    }
+
+    /// Witness TX known to the mempool but not mined yet.
+    pub fn mempool(tx: Tx) -> Self { Self::Resolved(tx, WitnessOrd::Tentative) }
+
+    /// Witness TX not found.
+    pub fn missing() -> Self { Self::Unresolved }
+
     /// Return the [`WitnessOrd`] for this [`WitnessStatus`].
     pub fn witness_ord(&self) -> WitnessOrd {
         match self {
@@ -125,11 +190,105 @@ impl<T: ResolveWitness> ResolveWitness for &T {
         ResolveWitness::resolve_witness(*self, witness_id)
     }
 
+    fn resolve_witness_at(
+        &self,
+        witness_id: Txid,
+        at_height: NonZeroU32,
+    ) -> Result<WitnessStatus, WitnessResolverError> {
+        ResolveWitness::resolve_witness_at(*self, witness_id, at_height)
+    }
+
+    fn resolve_witnesses(
+        &self,
+        witness_ids: &[Txid],
+    ) -> Result<Vec<WitnessStatus>, WitnessResolverError> {
+        ResolveWitness::resolve_witnesses(*self, witness_ids)
+    }
+
     fn check_chain_net(&self, chain_net: ChainNet) -> Result<(), WitnessResolverError> {
         ResolveWitness::check_chain_net(*self, chain_net)
     }
 }
 
+/// Resolver wrapper caching the resolved witnesses, so that each of them is queried to the inner
+/// resolver only once.
+pub struct CachingResolver<R: ResolveWitness> {
+    inner: R,
+    cache: RefCell<HashMap<Txid, WitnessStatus>>,
+    cache_at: RefCell<HashMap<(Txid, NonZeroU32), WitnessStatus>>,
+}
+
+impl<R: ResolveWitness> CachingResolver<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            cache: none!(),
+            cache_at: none!(),
+        }
+    }
+}
+
+impl<R: ResolveWitness> ResolveWitness for CachingResolver<R> {
+    fn resolve_witness(&self, witness_id: Txid) -> Result<WitnessStatus, WitnessResolverError> {
+        if let Some(witness_status) = self.cache.borrow().get(&witness_id) {
+            return Ok(witness_status.clone());
+        }
+        let witness_status = self.inner.resolve_witness(witness_id)?;
+        self.cache
+            .borrow_mut()
+            .insert(witness_id, witness_status.clone());
+        Ok(witness_status)
+    }
+
+    fn resolve_witness_at(
+        &self,
+        witness_id: Txid,
+        at_height: NonZeroU32,
+    ) -> Result<WitnessStatus, WitnessResolverError> {
+        if let Some(witness_status) = self.cache_at.borrow().get(&(witness_id, at_height)) {
+            return Ok(witness_status.clone());
+        }
+        let witness_status = self.inner.resolve_witness_at(witness_id, at_height)?;
+        self.cache_at
+            .borrow_mut()
+            .insert((witness_id, at_height), witness_status.clone());
+        Ok(witness_status)
+    }
+
+    fn resolve_witnesses(
+        &self,
+        witness_ids: &[Txid],
+    ) -> Result<Vec<WitnessStatus>, WitnessResolverError> {
+        let missing = {
+            let cache = self.cache.borrow();
+            let mut seen = HashSet::new();
+            witness_ids
+                .iter()
+                .copied()
+                .filter(|witness_id| !cache.contains_key(witness_id) && seen.insert(*witness_id))
+                .collect::<Vec<_>>()
+        };
+        if !missing.is_empty() {
+            let witness_statuses = self.inner.resolve_witnesses(&missing)?;
+            if witness_statuses.len() != missing.len() {
+                return Err(WitnessResolverError::InvalidResolverData);
+            }
+            self.cache
+                .borrow_mut()
+                .extend(missing.into_iter().zip(witness_statuses));
+        }
+        let cache = self.cache.borrow();
+        Ok(witness_ids
+            .iter()
+            .map(|witness_id| cache[witness_id].clone())
+            .collect())
+    }
+
+    fn check_chain_net(&self, chain_net: ChainNet) -> Result<(), WitnessResolverError> {
+        self.inner.check_chain_net(chain_net)
+    }
+}
+
 struct CheckedWitnessResolver<R: ResolveWitness> {
     inner: R,
 }
@@ -138,11 +297,12 @@ impl<R: ResolveWitness> From<R> for CheckedWitnessResolver<R> {
     fn from(inner: R) -> Self { Self { inner } }
 }
 
-impl<R: ResolveWitness> ResolveWitness for CheckedWitnessResolver<R> {
-    #[inline]
-    fn resolve_witness(&self, witness_id: Txid) -> Result<WitnessStatus, WitnessResolverError> {
-        let witness_status = self.inner.resolve_witness(witness_id)?;
-        if let WitnessStatus::Resolved(tx, _ord) = &witness_status {
+impl<R: ResolveWitness> CheckedWitnessResolver<R> {
+    fn check_witness_id(
+        witness_status: &WitnessStatus,
+        witness_id: Txid,
+    ) -> Result<(), WitnessResolverError> {
+        if let WitnessStatus::Resolved(tx, _ord) = witness_status {
             let actual_id = tx.compute_txid();
             if actual_id != witness_id {
                 return Err(WitnessResolverError::IdMismatch {
@@ -151,9 +311,42 @@ impl<R: ResolveWitness> ResolveWitness for CheckedWitnessResolver<R> {
                 });
             }
         }
+        Ok(())
+    }
+}
+
+impl<R: ResolveWitness> ResolveWitness for CheckedWitnessResolver<R> {
+    #[inline]
+    fn resolve_witness(&self, witness_id: Txid) -> Result<WitnessStatus, WitnessResolverError> {
+        let witness_status = self.inner.resolve_witness(witness_id)?;
+        Self::check_witness_id(&witness_status, witness_id)?;
         Ok(witness_status)
     }
 
+    fn resolve_witness_at(
+        &self,
+        witness_id: Txid,
+        at_height: NonZeroU32,
+    ) -> Result<WitnessStatus, WitnessResolverError> {
+        let witness_status = self.inner.resolve_witness_at(witness_id, at_height)?;
+        Self::check_witness_id(&witness_status, witness_id)?;
+        Ok(witness_status)
+    }
+
+    fn resolve_witnesses(
+        &self,
+        witness_ids: &[Txid],
+    ) -> Result<Vec<WitnessStatus>, WitnessResolverError> {
+        let witness_statuses = self.inner.resolve_witnesses(witness_ids)?;
+        if witness_statuses.len() != witness_ids.len() {
+            return Err(WitnessResolverError::InvalidResolverData);
+        }
+        for (witness_status, witness_id) in witness_statuses.iter().zip(witness_ids) {
+            Self::check_witness_id(witness_status, *witness_id)?;
+        }
+        Ok(witness_statuses)
+    }
+
     fn check_chain_net(&self, chain_net: ChainNet) -> Result<(), WitnessResolverError> {
         self.inner.check_chain_net(chain_net)
     }
@@ -163,8 +356,40 @@ impl<R: ResolveWitness> ResolveWitness for CheckedWitnessResolver<R> {
 pub struct ValidationConfig {
     pub chain_net: ChainNet,
     pub safe_height: Option<NonZeroU32>,
-    pub trusted_typesystem: TypeSystem,
+    pub trusted_typesystem: Arc<TypeSystem>,
     pub build_opouts_dag: bool,
+    /// If set, every script referenced by the schema must belong to this set.
+    pub trusted_lib_ids: Option<BTreeSet<LibId>>,
+    /// Maximum number of bundles a consignment may contain.
+    pub max_bundles: Option<usize>,
+    /// Maximum number of operations (including genesis) a consignment may contain.
+    pub max_operations: Option<usize>,
+    /// Minimum fee rate expected from unconfirmed witness transactions.
+    pub min_feerate: Option<FeeRate>,
+    /// Fail validation on the first warning, including [`Warning::UnsafeHistory`].
+    pub warnings_as_errors: bool,
+    /// Maximum size in bytes of each metadata and global state value.
+    pub max_value_bytes: Option<usize>,
+    /// Reject fungible assignments with zero value.
+    ///
+    /// NB: the policy applies to all the fungible types, since a schema can't mark a type as
+    /// allowing zero values without a change of the schema format. It must be left disabled for
+    /// contracts using zero-value assignments as markers.
+    pub forbid_zero_value: bool,
+    /// Maximum number of witnesses requested to the resolver with a single batch query.
+    pub max_concurrent_resolutions: Option<NonZeroUsize>,
+    /// Record the time spent in each validation phase into [`Status::timings`].
+    ///
+    /// NB: not supported on targets without a system clock, like `wasm32-unknown-unknown`.
+    pub record_timings: bool,
+    /// Contract the consignment is expected to be about.
+    pub expected_contract: Option<ContractId>,
+    /// If set, witnesses are resolved as they were at this blockchain height.
+    pub validation_height: Option<NonZeroU32>,
+    /// Witness ords reported by a previous validation. Witnesses no longer mined are reported
+    /// with [`Warning::WitnessOrdRegression`] if still known to the resolver, and with
+    /// [`Failure::WitnessOrdRegression`] otherwise.
+    pub prior_witness_ords: BTreeMap<Txid, WitnessOrd>,
 }
 
 pub struct Validator<
@@ -191,9 +416,20 @@ pub struct Validator<
 
     // Operations in this set will not be validated
     resolver: CheckedWitnessResolver<&'resolver R>,
+    witness_cache: RefCell<HashMap<Txid, WitnessStatus>>,
+    validation_height: Option<NonZeroU32>,
     safe_height: Option<NonZeroU32>,
-    trusted_typesystem: TypeSystem,
+    prior_witness_ords: BTreeMap<Txid, WitnessOrd>,
+    trusted_typesystem: Arc<TypeSystem>,
+    trusted_lib_ids: Option<BTreeSet<LibId>>,
+    min_feerate: Option<FeeRate>,
     opouts_dag_info: Option<RefCell<OpoutsDagInfo>>,
+    commitments_only: bool,
+    max_value_bytes: Option<usize>,
+    forbid_zero_value: bool,
+    max_concurrent_resolutions: Option<NonZeroUsize>,
+    // When set, only the bundles in this set are validated, the rest being trusted
+    bundle_subset: Option<BTreeSet<BundleId>>,
 }
 
 impl<
@@ -242,10 +478,20 @@ impl<
             input_opouts,
             opout_assigns,
             resolver: CheckedWitnessResolver::from(resolver),
+            witness_cache: none!(),
+            validation_height: validation_config.validation_height,
             contract_state: Rc::new(RefCell::new(S::init(context))),
             safe_height: validation_config.safe_height,
+            prior_witness_ords: validation_config.prior_witness_ords.clone(),
             trusted_typesystem: validation_config.trusted_typesystem.clone(),
+            trusted_lib_ids: validation_config.trusted_lib_ids.clone(),
+            min_feerate: validation_config.min_feerate,
             opouts_dag_info,
+            commitments_only: false,
+            max_value_bytes: validation_config.max_value_bytes,
+            forbid_zero_value: validation_config.forbid_zero_value,
+            max_concurrent_resolutions: validation_config.max_concurrent_resolutions,
+            bundle_subset: None,
         }
     }
 
@@ -258,31 +504,250 @@ impl<
         resolver: &'resolver R,
         context: S::Context<'_>,
         validation_config: &ValidationConfig,
+    ) -> Result<Status, ValidationError> {
+        Self::init(consignment, resolver, context, validation_config).run(validation_config)
+    }
+
+    fn run(mut self, validation_config: &ValidationConfig) -> Result<Status, ValidationError> {
+        trace_validation!(contract_id = %self.contract_id, "validating consignment");
+        if let Some(expected) = validation_config.expected_contract {
+            if self.contract_id != expected {
+                return Err(ValidationError::InvalidConsignment(Failure::UnexpectedContract {
+                    expected,
+                    actual: self.contract_id,
+                }));
+            }
+        }
+        self.check_chain_net(validation_config.chain_net)?;
+        // A schema not matching the one of the contract genesis makes the whole consignment
+        // invalid, so we fail before doing any witness resolution.
+        let schema_id = self.consignment.schema().schema_id();
+        if schema_id != self.schema_id {
+            return Err(ValidationError::InvalidConsignment(Failure::SchemaMismatch {
+                expected: self.schema_id,
+                actual: schema_id,
+            }));
+        }
+
+        let record = validation_config.record_timings;
+        let mut timings = PhaseTimings::default();
+
+        trace_validation!("checking consignment integrity");
+        timed(record, &mut timings.integrity, || self.validate_integrity(validation_config))?;
+
+        trace_validation!("validating schema");
+        timed(record, &mut timings.schema, || self.validate_schema())?;
+
+        trace_validation!("validating genesis");
+        timed(record, &mut timings.genesis, || self.validate_genesis())?;
+
+        trace_validation!("prefetching witnesses");
+        timed(record, &mut timings.witnesses, || self.prefetch_witnesses())?;
+
+        trace_validation!("validating bundles");
+        timed(record, &mut timings.bundles, || self.validate_bundles())?;
+
+        let mut status = self.status.into_inner();
+        if record {
+            status.timings = Some(timings);
+        }
+        trace_validation!(warnings = status.warnings.len(), "consignment validated");
+        if validation_config.warnings_as_errors {
+            if let Some(warning) = status.warnings.first() {
+                return Err(ValidationError::InvalidConsignment(Failure::WarningAsError(
+                    warning.clone(),
+                )));
+            }
+        }
+
+        // Done. Returning status report with all possible warnings and notifications.
+        Ok(status)
+    }
+
+    /// Validates a consignment trusting the type system it carries, for inspecting consignments
+    /// of schemas whose types are not known in advance.
+    ///
+    /// The type system is self-attested by the consignment, which is reported with
+    /// [`Info::SelfAttestedTypes`] in the returned status.
+    pub fn validate_self_contained(
+        consignment: &'consignment C,
+        resolver: &'resolver R,
+        context: S::Context<'_>,
+        chain_net: ChainNet,
+    ) -> Result<Status, ValidationError> {
+        let validation_config = ValidationConfig {
+            chain_net,
+            trusted_typesystem: Arc::new(consignment.types().clone()),
+            ..default!()
+        };
+        let mut status = Self::validate(consignment, resolver, context, &validation_config)?;
+        status.add_info(Info::SelfAttestedTypes);
+        Ok(status)
+    }
+
+    /// Validates only the commitment layer of a consignment: witness resolution, anchors and
+    /// single-use seal closing.
+    ///
+    /// Schema, state and script checks are skipped, so this must be used only to re-check
+    /// consignments whose logic has already been validated, e.g. after a chain reorg.
+    pub fn validate_commitments_only(
+        consignment: &'consignment C,
+        resolver: &'resolver R,
+        context: S::Context<'_>,
+        chain_net: ChainNet,
+    ) -> Result<Status, ValidationError> {
+        let validation_config = ValidationConfig {
+            chain_net,
+            ..default!()
+        };
+        let mut validator = Self::init(consignment, resolver, context, &validation_config);
+        validator.commitments_only = true;
+        validator.check_chain_net(chain_net)?;
+        validator.validate_integrity(&validation_config)?;
+        validator.validate_genesis()?;
+        validator.prefetch_witnesses()?;
+        validator.validate_bundles()?;
+        Ok(validator.status.into_inner())
+    }
+
+    /// Validates only the given bundles of a consignment: their witnesses, seal closing and the
+    /// logic of their transitions.
+    ///
+    /// Genesis and all the other bundles are trusted: their inputs are marked as spent, their
+    /// assignments are made available as inputs and they evolve the contract state without being
+    /// validated, so this must be used only to re-check specific transfers of consignments whose
+    /// remaining history has already been validated. Only the witnesses of the given bundles are
+    /// prefetched; trusted witnesses are ordered with `prior_witness_ords`, and resolved only if
+    /// missing from it. All the other options of `validation_config` apply as in
+    /// [`Self::validate`].
+    pub fn validate_bundle_subset(
+        consignment: &'consignment C,
+        resolver: &'resolver R,
+        context: S::Context<'_>,
+        validation_config: &ValidationConfig,
+        bundle_ids: &BTreeSet<BundleId>,
     ) -> Result<Status, ValidationError> {
         let mut validator = Self::init(consignment, resolver, context, validation_config);
+        let known_ids = consignment.bundle_ids().collect::<BTreeSet<_>>();
+        if let Some(bundle_id) = bundle_ids.difference(&known_ids).next() {
+            return Err(ValidationError::InvalidConsignment(Failure::BundleAbsent(*bundle_id)));
+        }
+        validator.bundle_subset = Some(bundle_ids.clone());
+        validator.run(validation_config)
+    }
+
+    /// Whether the bundle must be validated or is trusted in a bundle subset validation.
+    fn is_validated_bundle(&self, bundle_id: BundleId) -> bool {
+        self.bundle_subset
+            .as_ref()
+            .is_none_or(|subset| subset.contains(&bundle_id))
+    }
+
+    fn check_chain_net(&self, chain_net: ChainNet) -> Result<(), ValidationError> {
         // If the chain-network pair doesn't match there is no point in validating the contract
         // since all witness transactions will be missed.
-        if validator.chain_net != validation_config.chain_net {
+        if self.chain_net != chain_net {
             return Err(ValidationError::InvalidConsignment(Failure::ContractChainNetMismatch(
-                validation_config.chain_net,
+                chain_net,
             )));
         }
-        if let Err(e) = resolver.check_chain_net(validation_config.chain_net) {
+        if let Err(e) = self.resolver.check_chain_net(chain_net) {
             return Err(ValidationError::ResolverError(e));
         }
+        Ok(())
+    }
 
-        validator.validate_schema()?;
-
-        validator.validate_genesis()?;
-
-        validator.validate_bundles()?;
-
-        // Done. Returning status report with all possible warnings and notifications.
-        Ok(validator.status.into_inner())
+    // *** PART 0: Consignment integrity pre-checks
+    fn validate_integrity(
+        &self,
+        validation_config: &ValidationConfig,
+    ) -> Result<(), ValidationError> {
+        let mut bundles = 0usize;
+        // genesis is always present
+        let mut operations = 1usize;
+        let mut opids = bset![self.consignment.genesis().id()];
+        for (bundle, anchor, witness_id) in self.consignment.bundles_info() {
+            bundles += 1;
+            // The MPC proof position doesn't depend on the witness, so anchors placing the
+            // bundle at a position other than the contract one are rejected before any resolver
+            // query
+            let bundle_id = bundle.bundle_id();
+            if let Err(err) = anchor.convolve(self.contract_id, mpc::Message::from(bundle_id)) {
+                return Err(ValidationError::InvalidConsignment(Failure::MpcInvalid(
+                    bundle_id,
+                    witness_id,
+                    Box::new(err),
+                )));
+            }
+            operations += bundle.known_transitions.len();
+            for KnownTransition { opid, transition } in &bundle.known_transitions {
+                if !opids.insert(*opid) {
+                    return Err(ValidationError::InvalidConsignment(Failure::DuplicateOpId(*opid)));
+                }
+                if transition.contract_id() != self.contract_id {
+                    return Err(ValidationError::InvalidConsignment(
+                        Failure::BundleContractMismatch(bundle_id, transition.contract_id()),
+                    ));
+                }
+            }
+            // The input map size can be checked only when all the bundle transitions are known
+            if bundle.known_transitions_opids() == bundle.input_map_opids() {
+                let map_len = bundle.input_map.len();
+                let inputs_len = bundle
+                    .known_transitions
+                    .iter()
+                    .map(|KnownTransition { transition, .. }| transition.inputs.len())
+                    .sum::<usize>();
+                if map_len != inputs_len {
+                    return Err(ValidationError::InvalidConsignment(
+                        Failure::InputMapSizeMismatch {
+                            bundle_id,
+                            map_len,
+                            inputs_len,
+                        },
+                    ));
+                }
+            }
+            if let Some(limit) = validation_config.max_bundles {
+                if bundles > limit {
+                    return Err(ValidationError::InvalidConsignment(
+                        Failure::ConsignmentTooLarge {
+                            kind: ConsignmentItem::Bundles,
+                            count: bundles,
+                            limit,
+                        },
+                    ));
+                }
+            }
+            if let Some(limit) = validation_config.max_operations {
+                if operations > limit {
+                    return Err(ValidationError::InvalidConsignment(
+                        Failure::ConsignmentTooLarge {
+                            kind: ConsignmentItem::Operations,
+                            count: operations,
+                            limit,
+                        },
+                    ));
+                }
+            }
+        }
+        Ok(())
     }
 
     // *** PART I: Schema validation
     fn validate_schema(&mut self) -> Result<(), ValidationError> {
+        let types = self.consignment.types();
+        for (_, ty) in types.iter() {
+            if let Some(sem_id) = ty
+                .iter()
+                .map(|(sem_id, _)| *sem_id)
+                .find(|sem_id| types.get(*sem_id).is_none())
+            {
+                return Err(ValidationError::InvalidConsignment(Failure::IncompleteTypeSystem(
+                    sem_id,
+                )));
+            }
+        }
         for (sem_id, consignment_type) in self.consignment.types().iter() {
             let trusted_type = self.trusted_typesystem.get(*sem_id);
             if trusted_type != Some(consignment_type) {
@@ -294,6 +759,58 @@ impl<
             }
         }
         self.consignment.schema().verify(self.consignment.types())?;
+        for (transition_type, assignment_type) in self.consignment.schema().unsatisfiable_inputs() {
+            self.status
+                .borrow_mut()
+                .add_warning(Warning::UnsatisfiableInput(transition_type, assignment_type));
+        }
+        if let Some(trusted_lib_ids) = &self.trusted_lib_ids {
+            if let Some(lib_id) = self
+                .consignment
+                .schema()
+                .libs()
+                .find(|lib_id| !trusted_lib_ids.contains(lib_id))
+            {
+                return Err(ValidationError::InvalidConsignment(Failure::UntrustedScript(lib_id)));
+            }
+        }
+        self.check_script_reachability()
+    }
+
+    /// Fails if any script used by the schema validators is missing from the consignment, and
+    /// warns about missing scripts they import and about the consignment scripts which can't be
+    /// reached.
+    fn check_script_reachability(&self) -> Result<(), ValidationError> {
+        let validator_libs = self.consignment.schema().libs().collect::<BTreeSet<_>>();
+        let missing = validator_libs
+            .iter()
+            .filter(|lib_id| !self.scripts.contains_key(*lib_id))
+            .copied()
+            .collect::<BTreeSet<_>>();
+        if !missing.is_empty() {
+            return Err(ValidationError::InvalidConsignment(Failure::MissingScripts(missing)));
+        }
+        let mut status = self.status.borrow_mut();
+        let mut reachable = BTreeSet::new();
+        let mut queue = validator_libs.into_iter().collect::<Vec<_>>();
+        while let Some(lib_id) = queue.pop() {
+            if !reachable.insert(lib_id) {
+                continue;
+            }
+            match self.scripts.get(&lib_id) {
+                Some(lib) => queue.extend(lib.libs.iter().copied()),
+                // Imports are looked up only when called, so a missing one makes the validation
+                // fail only if a script actually calls into it
+                None => {
+                    status.add_warning(Warning::MissingImportedScript(lib_id));
+                }
+            }
+        }
+        for lib_id in self.scripts.keys() {
+            if !reachable.contains(lib_id) {
+                status.add_warning(Warning::UnreachableScript(*lib_id));
+            }
+        }
         Ok(())
     }
 
@@ -301,27 +818,83 @@ impl<
     fn validate_genesis(&mut self) -> Result<(), ValidationError> {
         let schema = self.consignment.schema();
 
-        // [VALIDATION]: Making sure that we were supplied with the schema
-        //               that corresponds to the schema of the contract genesis
-        if schema.schema_id() != self.schema_id {
-            return Err(ValidationError::InvalidConsignment(Failure::SchemaMismatch {
-                expected: self.schema_id,
-                actual: schema.schema_id(),
-            }));
-        }
-
         // [VALIDATION]: Validate genesis
         let genesis = self.consignment.genesis().clone();
-        schema.validate_state(
-            self.consignment.types(),
-            &self.scripts,
-            self.consignment.genesis(),
-            OrdOpRef::Genesis(&genesis),
-            self.contract_state.clone(),
-            &BTreeMap::new(),
-        )?;
+        if !self.commitments_only && self.bundle_subset.is_none() {
+            self.check_value_sizes(genesis.id(), &genesis)?;
+            self.check_zero_values(genesis.id(), &genesis.assignments)?;
+            schema.validate_state(
+                self.consignment.types(),
+                &self.scripts,
+                self.consignment.genesis(),
+                OrdOpRef::Genesis(&genesis),
+                self.contract_state.clone(),
+                &BTreeMap::new(),
+            )?;
+        } else if self.bundle_subset.is_some() {
+            self.evolve_trusted_state(genesis.id(), OrdOpRef::Genesis(&genesis))?;
+        }
         let contract_id = genesis.id();
         self.process_assignments(contract_id, None, &genesis.assignments)?;
+        trace_validation!(opid = %contract_id, "validated genesis");
+        Ok(())
+    }
+
+    /// Checks that no metadata or global state value of the operation exceeds the configured
+    /// maximum size.
+    fn check_value_sizes(&self, opid: OpId, op: &impl Operation) -> Result<(), ValidationError> {
+        let Some(limit) = self.max_value_bytes else {
+            return Ok(());
+        };
+        for (meta_type, value) in op.metadata().iter() {
+            let size = value.len();
+            if size > limit {
+                return Err(ValidationError::InvalidConsignment(Failure::MetadataTooLarge {
+                    opid,
+                    meta_type: *meta_type,
+                    size,
+                    limit,
+                }));
+            }
+        }
+        for (global_type, values) in op.globals().iter() {
+            if let Some(size) = values
+                .iter()
+                .map(|value| value.len())
+                .find(|size| *size > limit)
+            {
+                return Err(ValidationError::InvalidConsignment(Failure::GlobalValueTooLarge {
+                    opid,
+                    global_type: *global_type,
+                    size,
+                    limit,
+                }));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that no fungible assignment of the operation has zero value, if forbidden.
+    fn check_zero_values(
+        &self,
+        opid: OpId,
+        assignments: &Assignments<impl ExposedSeal>,
+    ) -> Result<(), ValidationError> {
+        if !self.forbid_zero_value {
+            return Ok(());
+        }
+        for (state_type, typed_assigns) in assignments.iter() {
+            if typed_assigns
+                .as_fungible()
+                .iter()
+                .any(|assign| assign.as_revealed_state().as_u64() == 0)
+            {
+                return Err(ValidationError::InvalidConsignment(Failure::ZeroValueAssignment(
+                    opid,
+                    *state_type,
+                )));
+            }
+        }
         Ok(())
     }
 
@@ -353,79 +926,252 @@ impl<
     }
 
     // *** PART III: Validating single-use-seals
+    /// Resolves all the witnesses of the consignment with batch queries, so that resolvers able to
+    /// run queries concurrently don't pay the latency of each of them in sequence.
+    ///
+    /// Batches are limited to `max_concurrent_resolutions` witnesses, bounding the number of
+    /// queries a resolver has in flight at the same time.
+    fn prefetch_witnesses(&self) -> Result<(), ValidationError> {
+        // Batch queries have no height-pinned counterpart
+        if self.validation_height.is_some() {
+            return Ok(());
+        }
+        let mut seen = HashSet::new();
+        let witness_ids = self
+            .consignment
+            .bundles_info()
+            .filter(|(bundle, _, _)| self.is_validated_bundle(bundle.bundle_id()))
+            .map(|(_, _, witness_id)| witness_id)
+            .filter(|witness_id| seen.insert(*witness_id))
+            .collect::<Vec<_>>();
+        let batch_size = self
+            .max_concurrent_resolutions
+            .map(NonZeroUsize::get)
+            .unwrap_or(witness_ids.len())
+            .max(1);
+        for batch in witness_ids.chunks(batch_size) {
+            let witness_statuses = self
+                .resolver
+                .resolve_witnesses(batch)
+                .map_err(ValidationError::ResolverError)?;
+            self.witness_cache
+                .borrow_mut()
+                .extend(batch.iter().copied().zip(witness_statuses));
+        }
+        Ok(())
+    }
+
     fn validate_bundles(&mut self) -> Result<(), ValidationError> {
-        let mut unsafe_history_map: HashMap<u32, HashSet<Txid>> = HashMap::new();
+        let mut unsafe_history = UnsafeHistory::default();
         for (bundle, anchor, witness_id) in self.consignment.bundles_info() {
             let bundle_id = bundle.bundle_id();
+            if !self.is_validated_bundle(bundle_id) {
+                let witness_ord = match self.prior_witness_ords.get(&witness_id) {
+                    Some(witness_ord) => *witness_ord,
+                    None => self
+                        .witness_status(witness_id)
+                        .map_err(ValidationError::ResolverError)?
+                        .witness_ord(),
+                };
+                for KnownTransition { opid, transition } in &bundle.known_transitions {
+                    let op = OrdOpRef::Transition(transition, witness_id, witness_ord, bundle_id);
+                    self.evolve_trusted_state(*opid, op)?;
+                    // Trusted transitions still spend their inputs, so that validated ones can't
+                    // spend them again
+                    for input in &transition.inputs {
+                        self.opout_assigns.borrow_mut().remove(&input);
+                        if !self.input_opouts.borrow_mut().insert(input) {
+                            return Err(ValidationError::InvalidConsignment(Failure::CyclicGraph(
+                                input,
+                            )));
+                        }
+                    }
+                    self.process_assignments(*opid, Some(witness_id), &transition.assignments)?;
+                    if let Some(ref mut dag_info) = self.opouts_dag_info {
+                        dag_info.borrow_mut().connect_transition(transition, opid);
+                    }
+                }
+                continue;
+            }
             let (witness_tx, witness_ord) = self.resolve_witness(bundle_id, witness_id)?;
+            if let Some(min_feerate) = self.min_feerate {
+                if witness_ord == WitnessOrd::Tentative {
+                    self.check_witness_feerate(&witness_tx, min_feerate);
+                }
+            }
             if let Some(safe_height) = self.safe_height {
                 match witness_ord {
                     WitnessOrd::Mined(witness_pos) => {
                         let witness_height = witness_pos.height();
                         if witness_height > safe_height {
-                            unsafe_history_map
+                            unsafe_history
+                                .above_safe_height
                                 .entry(witness_height.into())
                                 .or_default()
                                 .insert(witness_id);
                         }
                     }
                     WitnessOrd::Tentative | WitnessOrd::Ignored | WitnessOrd::Archived => {
-                        unsafe_history_map.entry(0).or_default().insert(witness_id);
+                        unsafe_history.unconfirmed.insert(witness_id);
                     }
                 }
             }
+            trace_validation!(%bundle_id, %witness_id, %witness_ord, "validating bundle");
             for known_transition in &bundle.known_transitions {
-                self.validate_transition(
+                let KnownTransition { opid, transition } = known_transition;
+                let res = self.validate_transition(
                     known_transition,
                     bundle,
                     &witness_tx,
                     &witness_ord,
                     anchor,
-                )?;
-                let KnownTransition { opid, transition } = known_transition;
+                );
+                trace_validation!(
+                    %opid,
+                    %bundle_id,
+                    %witness_id,
+                    valid = res.is_ok(),
+                    "validated transition"
+                );
+                res?;
                 self.process_assignments(*opid, Some(witness_id), &transition.assignments)?;
                 if let Some(ref mut dag_info) = self.opouts_dag_info {
                     dag_info.borrow_mut().connect_transition(transition, opid);
                 }
             }
         }
-        if self.safe_height.is_some() && !unsafe_history_map.is_empty() {
+        // A resolver silently switching network mid-session would have served witnesses from
+        // another chain, so the chain-network assertion is repeated once they are all resolved
+        if self.consignment.bundles_info().next().is_some() {
+            match self.resolver.check_chain_net(self.chain_net) {
+                Ok(()) => {}
+                Err(WitnessResolverError::WrongChainNet) => {
+                    return Err(ValidationError::InvalidConsignment(
+                        Failure::ResolverChainNetChanged(self.chain_net),
+                    ));
+                }
+                Err(e) => return Err(ValidationError::ResolverError(e)),
+            }
+        }
+        if self.safe_height.is_some() && !unsafe_history.is_empty() {
             self.status
                 .borrow_mut()
-                .add_warning(Warning::UnsafeHistory(unsafe_history_map));
+                .add_warning(Warning::UnsafeHistory(unsafe_history));
         }
+        self.status.borrow_mut().spent_opouts = self.input_opouts.borrow().clone();
         if let Some(dag_info) = &self.opouts_dag_info {
             self.status.borrow_mut().dag_data_opt = Some(dag_info.borrow().to_opouts_dag_data());
         }
         Ok(())
     }
 
+    /// Returns the prefetched status of the witness, or queries it to the resolver.
+    fn witness_status(&self, witness_id: Txid) -> Result<WitnessStatus, WitnessResolverError> {
+        let cached = self.witness_cache.borrow().get(&witness_id).cloned();
+        match cached {
+            Some(witness_status) => Ok(witness_status),
+            None => match self.validation_height {
+                Some(at_height) => self.resolver.resolve_witness_at(witness_id, at_height),
+                None => self.resolver.resolve_witness(witness_id),
+            },
+        }
+    }
+
+    /// Evolves the contract state with a trusted operation, which is not validated.
+    fn evolve_trusted_state(&self, opid: OpId, op: OrdOpRef) -> Result<(), ValidationError> {
+        if let Err(err) = self.contract_state.borrow_mut().evolve_state(op) {
+            return Err(ValidationError::InvalidConsignment(Failure::ContractStateError(
+                opid,
+                err.to_string(),
+            )));
+        }
+        Ok(())
+    }
+
     fn resolve_witness(
         &self,
         bundle_id: BundleId,
         witness_id: Txid,
     ) -> Result<(Tx, WitnessOrd), ValidationError> {
-        match self.resolver.resolve_witness(witness_id) {
-            Err(err) => {
-                // Unable to retrieve the corresponding transaction from the resolver.
-                // Reporting this incident immediately.
-                Err(ValidationError::ResolverError(err))
-            }
-            Ok(witness_status) => match witness_status {
-                WitnessStatus::Resolved(tx, ord) if ord != WitnessOrd::Archived => {
-                    self.status
-                        .borrow_mut()
-                        .tx_ord_map
-                        .insert(tx.compute_txid(), ord);
-                    Ok((tx, ord))
+        // Unable to retrieve the corresponding transaction from the resolver.
+        // Reporting this incident immediately.
+        let witness_status = self
+            .witness_status(witness_id)
+            .map_err(ValidationError::ResolverError)?;
+        let now = witness_status.witness_ord();
+        let regressed_from = self
+            .prior_witness_ords
+            .get(&witness_id)
+            .copied()
+            .filter(|prev| {
+                matches!(prev, WitnessOrd::Mined(_)) && !matches!(now, WitnessOrd::Mined(_))
+            });
+        match witness_status {
+            WitnessStatus::Resolved(tx, ord) if ord != WitnessOrd::Archived => {
+                if let Some(prev) = regressed_from {
+                    let warning = Warning::WitnessOrdRegression(witness_id, prev, now);
+                    let mut status = self.status.borrow_mut();
+                    if !status.warnings.contains(&warning) {
+                        status.add_warning(warning);
+                    }
                 }
-                _ => Err(ValidationError::InvalidConsignment(Failure::SealNoPubWitness(
+                self.status
+                    .borrow_mut()
+                    .tx_ord_map
+                    .insert(tx.compute_txid(), ord);
+                Ok((tx, ord))
+            }
+            // A witness which was mined is reported with its previous status, since it has
+            // likely been reorged out rather than never published
+            _ => match regressed_from {
+                Some(prev) => Err(ValidationError::InvalidConsignment(
+                    Failure::WitnessOrdRegression(bundle_id, witness_id, prev, now),
+                )),
+                None => Err(ValidationError::InvalidConsignment(Failure::SealNoPubWitness(
                     bundle_id, witness_id,
                 ))),
             },
         }
     }
 
+    /// Checks the fee rate paid by a witness transaction, warning if it is below the provided
+    /// minimum.
+    ///
+    /// Amounts of the spent outputs are retrieved by resolving the transactions which created
+    /// them; if any of them is unknown to the resolver, or the resolver fails to retrieve it, the
+    /// fee can't be computed and the check is skipped.
+    fn check_witness_feerate(&self, witness_tx: &Tx, min_feerate: FeeRate) {
+        let mut input_value = Amount::ZERO;
+        for input in &witness_tx.input {
+            let prevout = input.previous_output;
+            let Ok(WitnessStatus::Resolved(prev_tx, _)) =
+                self.resolver.resolve_witness(prevout.txid)
+            else {
+                return;
+            };
+            let Some(spent) = prev_tx.output.get(prevout.vout as usize) else {
+                return;
+            };
+            let Some(value) = input_value.checked_add(spent.value) else {
+                return;
+            };
+            input_value = value;
+        }
+        let output_value = witness_tx
+            .output
+            .iter()
+            .try_fold(Amount::ZERO, |sum, out| sum.checked_add(out.value));
+        let Some(fee) = output_value.and_then(|value| input_value.checked_sub(value)) else {
+            return;
+        };
+        let feerate = fee / witness_tx.weight();
+        if feerate < min_feerate {
+            self.status
+                .borrow_mut()
+                .add_warning(Warning::LowFeeWitness(witness_tx.compute_txid(), feerate));
+        }
+    }
+
     /// Single-use-seal closing validation.
     ///
     /// Checks that the set of seals is closed over the message, which is
@@ -438,60 +1184,29 @@ impl<
     /// the bundle under the current contract.
     fn validate_seal_closing<Dbc: dbc::Proof>(
         &self,
-        seals: BTreeSet<BlindSeal<Txid>>,
+        seals: &BTreeSet<BlindSeal<Txid>>,
         bundle_id: BundleId,
         witness: &Witness<Dbc>,
         mpc_proof: mpc::MerkleProof,
-    ) -> Result<(), ValidationError>
-    where
-        Witness<Dbc>: SealWitness<BlindSeal<Txid>, Message = mpc::Commitment>,
-    {
-        let message = mpc::Message::from(bundle_id);
-        let anchor = Anchor::new(mpc_proof, witness.proof.clone());
-        // [VALIDATION]: Checking anchor MPC commitment
-        match anchor.convolve(self.contract_id, message) {
-            Err(err) => {
-                // The operation is not committed to bitcoin transaction graph!
-                // Ultimate failure. But continuing to detect the rest (after reporting it).
-                return Err(ValidationError::InvalidConsignment(Failure::MpcInvalid(
-                    bundle_id,
-                    witness.txid,
-                    Box::new(err),
-                )));
-            }
-            Ok(commitment) => {
-                // [VALIDATION]: Verify commitment
-                let Some(output) =
-                    witness.tx.output.iter().find(|out| {
-                        out.script_pubkey.is_op_return() || out.script_pubkey.is_p2tr()
-                    })
-                else {
-                    return Err(ValidationError::InvalidConsignment(Failure::NoDbcOutput(
-                        witness.txid,
-                    )));
-                };
-                let output_method = if output.script_pubkey.is_op_return() {
-                    CloseMethod::OpretFirst
-                } else {
-                    CloseMethod::TapretFirst
-                };
-                let proof_method = witness.proof.method();
-                if proof_method != output_method {
-                    return Err(ValidationError::InvalidConsignment(Failure::InvalidProofType(
-                        witness.txid,
-                        proof_method,
-                    )));
+    ) -> Result<(), ValidationError> {
+        check_seals_closing(seals, self.contract_id, bundle_id, witness, mpc_proof)
+            .map_err(ValidationError::InvalidConsignment)?;
+        // Opret outputs carry no value by design, while a sub-dust tapret output makes the
+        // witness non-standard
+        if let Some(output) = witness
+            .tx
+            .output
+            .iter()
+            .find(|out| out.script_pubkey.is_op_return() || out.script_pubkey.is_p2tr())
+        {
+            if output.script_pubkey.is_p2tr()
+                && output.value < output.script_pubkey.minimal_non_dust()
+            {
+                let warning = Warning::DustCommitmentOutput(witness.txid, output.value);
+                let mut status = self.status.borrow_mut();
+                if !status.warnings.contains(&warning) {
+                    status.add_warning(warning);
                 }
-                // [VALIDATION]: CHECKING SINGLE-USE-SEALS
-                witness
-                    .verify_many_seals(seals.iter(), &commitment)
-                    .map_err(|err| {
-                        ValidationError::InvalidConsignment(Failure::SealsInvalid(
-                            bundle_id,
-                            witness.txid,
-                            err.to_string(),
-                        ))
-                    })?;
             }
         }
         Ok(())
@@ -513,12 +1228,8 @@ impl<
                 transition.id(),
             )));
         }
-        if transition.contract_id() != self.contract_id {
-            return Err(ValidationError::InvalidConsignment(Failure::ContractMismatch(
-                opid,
-                transition.contract_id(),
-            )));
-        }
+        // Transitions of other contracts are rejected with BundleContractMismatch by the
+        // integrity pre-checks, before any transition gets validated
         let bundle_id = bundle.bundle_id();
 
         let mut state_by_type = BTreeMap::<AssignmentType, Vec<RevealedState>>::new();
@@ -541,8 +1252,34 @@ impl<
                 return Err(ValidationError::InvalidConsignment(Failure::CyclicGraph(input)));
             };
         }
+        // [VALIDATION]: Checking that the input map doesn't assign to this transition opouts it
+        //               doesn't spend
+        if let Some((opout, _)) = bundle
+            .input_map
+            .iter()
+            .find(|(opout, id)| **id == opid && !transition.inputs.contains(opout))
+        {
+            return Err(ValidationError::InvalidConsignment(Failure::InputMapMismatch(
+                bundle_id, opid, *opout,
+            )));
+        }
         let witness = Witness::with(witness_tx.clone(), anchor.dbc_proof.clone());
-        self.validate_seal_closing(seals, bundle_id, &witness, anchor.mpc_proof.clone())?;
+        self.validate_seal_closing(&seals, bundle_id, &witness, anchor.mpc_proof.clone())?;
+        self.status
+            .borrow_mut()
+            .seals_by_witness
+            .entry(witness.txid)
+            .or_default()
+            .extend(
+                seals
+                    .into_iter()
+                    .map(|seal| seal.to_output_seal_or_default(witness.txid)),
+            );
+        if self.commitments_only {
+            return Ok(());
+        }
+        self.check_value_sizes(opid, transition)?;
+        self.check_zero_values(opid, &transition.assignments)?;
         self.consignment.schema().validate_state(
             self.consignment.types(),
             &self.scripts,
@@ -554,3 +1291,757 @@ impl<
         Ok(())
     }
 }
+
+/// Validates the closing of a single seal by a witness transaction.
+///
+/// Checks that the witness spends the seal outpoint and that, through the provided MPC proof and
+/// the witness DBC proof, it commits to the bundle under the given contract.
+pub fn validate_single_seal<Dbc: dbc::Proof>(
+    seal: &OutputSeal,
+    witness: &Witness<Dbc>,
+    mpc_proof: mpc::MerkleProof,
+    contract_id: ContractId,
+    bundle_id: BundleId,
+) -> Result<(), Failure> {
+    check_seals_closing([seal], contract_id, bundle_id, witness, mpc_proof)
+}
+
+fn check_seals_closing<'seal, Seal: TxoSeal + 'seal, Dbc: dbc::Proof>(
+    seals: impl IntoIterator<Item = &'seal Seal>,
+    contract_id: ContractId,
+    bundle_id: BundleId,
+    witness: &Witness<Dbc>,
+    mpc_proof: mpc::MerkleProof,
+) -> Result<(), Failure> {
+    let message = mpc::Message::from(bundle_id);
+    let anchor = Anchor::new(mpc_proof, witness.proof.clone());
+    // [VALIDATION]: Checking anchor MPC commitment
+    let commitment = anchor
+        .convolve(contract_id, message)
+        .map_err(|err| Failure::MpcInvalid(bundle_id, witness.txid, Box::new(err)))?;
+    // [VALIDATION]: Verify commitment
+    let Some(output) = witness
+        .tx
+        .output
+        .iter()
+        .find(|out| out.script_pubkey.is_op_return() || out.script_pubkey.is_p2tr())
+    else {
+        return Err(Failure::NoDbcOutput(
+            witness.txid,
+            witness
+                .tx
+                .output
+                .iter()
+                .map(|out| ScriptType::from(out.script_pubkey.as_script()))
+                .collect(),
+        ));
+    };
+    let output_method = if output.script_pubkey.is_op_return() {
+        CloseMethod::OpretFirst
+    } else {
+        CloseMethod::TapretFirst
+    };
+    let proof_method = witness.proof.method();
+    if proof_method != output_method {
+        return Err(Failure::InvalidProofType(witness.txid, proof_method));
+    }
+    // [VALIDATION]: CHECKING SINGLE-USE-SEALS
+    witness
+        .verify_many_seals(seals, &commitment)
+        .map_err(|err| Failure::SealsInvalid(bundle_id, witness.txid, err.to_string()))
+}
+
+/// Runs `f`, storing its duration into `duration` if `enabled`.
+fn timed<T>(enabled: bool, duration: &mut Duration, f: impl FnOnce() -> T) -> T {
+    if !enabled {
+        return f();
+    }
+    let start = Instant::now();
+    let res = f();
+    *duration = start.elapsed();
+    res
+}
+
+/// Validates multiple consignments sharing a single witness cache, so that a witness referenced
+/// by several of them is resolved only once.
+///
+/// Each consignment is validated independently with its own context, and results are returned in
+/// the same order of the provided consignments.
+pub fn validate_many<'consignment, 'ctx, S, C, R>(
+    consignments: impl IntoIterator<Item = (&'consignment C, S::Context<'ctx>)>,
+    resolver: &R,
+    validation_config: &ValidationConfig,
+) -> Vec<Result<Status, ValidationError>>
+where
+    S: ContractStateAccess + ContractStateEvolve,
+    C: ConsignmentApi + 'consignment,
+    R: ResolveWitness,
+{
+    let resolver = CachingResolver::new(resolver);
+    consignments
+        .into_iter()
+        .map(|(consignment, context)| {
+            Validator::<S, C, _>::validate(consignment, &resolver, context, validation_config)
+        })
+        .collect()
+}
+
+/// Computes the contract state from a consignment without validating it.
+///
+/// Operations are applied to the state in the same order used by [`Validator::validate`], but no
+/// schema, commitment or script checks are performed: the function must be used only with
+/// consignments which have already passed the validation.
+pub fn compute_state<S: ContractStateAccess + ContractStateEvolve>(
+    consignment: &impl ConsignmentApi,
+    context: S::Context<'_>,
+    ord_provider: &impl WitnessOrdProvider,
+) -> Result<S, ValidationError> {
+    let mut state = S::init(context);
+
+    let genesis = consignment.genesis();
+    if let Err(err) = state.evolve_state(OrdOpRef::Genesis(genesis)) {
+        return Err(ValidationError::InvalidConsignment(Failure::ContractStateError(
+            genesis.id(),
+            err.to_string(),
+        )));
+    }
+
+    for (bundle, _, witness_id) in consignment.bundles_info() {
+        let bundle_id = bundle.bundle_id();
+        let witness_ord = ord_provider
+            .witness_ord(witness_id)
+            .map_err(ValidationError::ResolverError)?;
+        for KnownTransition { opid, transition } in &bundle.known_transitions {
+            let op = OrdOpRef::Transition(transition, witness_id, witness_ord, bundle_id);
+            if let Err(err) = state.evolve_state(op) {
+                return Err(ValidationError::InvalidConsignment(Failure::ContractStateError(
+                    *opid,
+                    err.to_string(),
+                )));
+            }
+        }
+    }
+
+    Ok(state)
+}
+
+#[cfg(test)]
+mod test {
+    use std::borrow::Borrow;
+    use std::convert::Infallible;
+    use std::iter;
+
+    use aluvm::library::Lib;
+    use amplify::confinement::{Confined, NonEmptyOrdMap, NonEmptyOrdSet, NonEmptyVec};
+    use bitcoin::absolute::LockTime;
+    use bitcoin::transaction::Version;
+    use bitcoin::{OutPoint as Outpoint, ScriptBuf, TxIn, TxOut};
+    use strict_encoding::StrictDumb;
+
+    use super::*;
+    use crate::assignments::AssignVec;
+    use crate::dbc::opret::OpretProof;
+    use crate::validation::{
+        EAnchor, ProvenanceError, ProvenanceNode, RecordingResolver, ReplayResolver, Validity,
+    };
+    use crate::vm::{GlobalStateEntry, GlobalsIter, UnknownGlobalStateType};
+    use crate::{
+        Assign, AssignmentDetails, FungibleState, Genesis, GenesisSchema, GenesisSeal,
+        GlobalStateType, GraphSeal, Inputs, Occurrences, OwnedStateSchema, RevealedData, Schema,
+        Transition, TransitionDetails, TransitionSchema, TransitionType, TypedAssigns,
+    };
+
+    const RIGHT: AssignmentType = AssignmentType::with(1);
+    const TRANSFER: TransitionType = TransitionType::with(1);
+
+    #[derive(Debug)]
+    struct NoState;
+
+    struct NoGlobals;
+
+    impl Iterator for NoGlobals {
+        type Item = GlobalStateEntry;
+
+        fn next(&mut self) -> Option<Self::Item> { None }
+    }
+
+    impl GlobalsIter for NoGlobals {
+        fn at_depth(&self, _: usize) -> Option<Self::Item> { None }
+    }
+
+    impl ContractStateAccess for NoState {
+        fn global(
+            &self,
+            ty: GlobalStateType,
+        ) -> Result<impl GlobalsIter<Item = impl Borrow<GlobalStateEntry>>, UnknownGlobalStateType>
+        {
+            Err::<NoGlobals, _>(UnknownGlobalStateType(ty))
+        }
+
+        fn rights(&self, _: Outpoint, _: AssignmentType) -> u32 { 0 }
+
+        fn fungible(
+            &self,
+            _: Outpoint,
+            _: AssignmentType,
+        ) -> impl DoubleEndedIterator<Item = FungibleState> {
+            iter::empty()
+        }
+
+        fn data(
+            &self,
+            _: Outpoint,
+            _: AssignmentType,
+        ) -> impl DoubleEndedIterator<Item = impl Borrow<RevealedData>> {
+            iter::empty::<RevealedData>()
+        }
+    }
+
+    impl ContractStateEvolve for NoState {
+        type Context<'ctx> = ();
+        type Error = Infallible;
+
+        fn init(_: ()) -> Self { NoState }
+
+        fn evolve_state(&mut self, _: OrdOpRef) -> Result<(), Infallible> { Ok(()) }
+    }
+
+    struct TestConsignment {
+        schema: Schema,
+        types: TypeSystem,
+        genesis: Genesis,
+        bundles: Vec<(TransitionBundle, EAnchor, Txid)>,
+    }
+
+    impl TestConsignment {
+        fn with_witnesses(witness_ids: &[Txid]) -> Self {
+            Self {
+                schema: Schema::strict_dumb(),
+                types: TypeSystem::default(),
+                genesis: Genesis::strict_dumb(),
+                bundles: witness_ids
+                    .iter()
+                    .map(|id| (TransitionBundle::strict_dumb(), EAnchor::strict_dumb(), *id))
+                    .collect(),
+            }
+        }
+    }
+
+    impl ConsignmentApi for TestConsignment {
+        fn schema(&self) -> &Schema { &self.schema }
+
+        fn types(&self) -> &TypeSystem { &self.types }
+
+        fn scripts(&self) -> impl Iterator<Item = &Lib> { iter::empty() }
+
+        fn genesis(&self) -> &Genesis { &self.genesis }
+
+        fn bundles_info(&self) -> impl Iterator<Item = (&TransitionBundle, &EAnchor, Txid)> {
+            self.bundles
+                .iter()
+                .map(|(bundle, anchor, id)| (bundle, anchor, *id))
+        }
+    }
+
+    /// Contract history with valid commitments, transferring declarative rights.
+    struct TestContract {
+        consignment: TestConsignment,
+        resolver: CountingResolver,
+        outpoints: BTreeMap<Opout, Outpoint>,
+    }
+
+    impl TestContract {
+        /// Issues a contract with a single right, returning it along with the right.
+        fn issue() -> (Self, Opout) {
+            let schema = Schema {
+                ffv: default!(),
+                name: tn!("TestRights"),
+                meta_types: none!(),
+                global_types: none!(),
+                owned_types: tiny_bmap! {
+                    RIGHT => AssignmentDetails {
+                        owned_state_schema: OwnedStateSchema::Declarative,
+                        name: fname!("right"),
+                        default_transition: TRANSFER,
+                    }
+                },
+                genesis: GenesisSchema {
+                    assignments: tiny_bmap! { RIGHT => Occurrences::OnceOrMore },
+                    ..default!()
+                },
+                transitions: tiny_bmap! {
+                    TRANSFER => TransitionDetails {
+                        transition_schema: TransitionSchema {
+                            inputs: tiny_bmap! { RIGHT => Occurrences::OnceOrMore },
+                            assignments: tiny_bmap! { RIGHT => Occurrences::OnceOrMore },
+                            ..default!()
+                        },
+                        name: fname!("transfer"),
+                    }
+                },
+                default_assignment: None,
+            };
+            let issue_txid = Tx::strict_dumb().compute_txid();
+            let genesis = Genesis {
+                schema_id: schema.schema_id(),
+                assignments: rights(GenesisSeal::with_blinding(issue_txid, 0u32, 0)),
+                ..Genesis::strict_dumb()
+            };
+            let right = Opout::new(genesis.id(), RIGHT, 0);
+            let contract = Self {
+                consignment: TestConsignment {
+                    schema,
+                    types: none!(),
+                    genesis,
+                    bundles: none!(),
+                },
+                resolver: default!(),
+                outpoints: bmap! { right => Outpoint::new(issue_txid, 0) },
+            };
+            (contract, right)
+        }
+
+        /// Adds a bundle with a single transition spending the given rights into a new one, which
+        /// is returned along with the bundle id.
+        fn transfer(&mut self, rights: &[Opout]) -> (BundleId, Opout) {
+            let contract_id = self.consignment.genesis.contract_id();
+            let nonce = self.consignment.bundles.len() as u64;
+            let transition = Transition {
+                ffv: default!(),
+                contract_id,
+                nonce,
+                transition_type: TRANSFER,
+                metadata: none!(),
+                globals: none!(),
+                inputs: Inputs::from(
+                    NonEmptyOrdSet::try_from_iter(rights.iter().copied()).unwrap(),
+                ),
+                assignments: self::rights(GraphSeal::with_blinded_vout(0u32, nonce)),
+                signature: None,
+            };
+            let opid = transition.id();
+            let bundle = TransitionBundle {
+                input_map: NonEmptyOrdMap::try_from_iter(rights.iter().map(|right| (*right, opid)))
+                    .unwrap(),
+                known_transitions: NonEmptyVec::with(KnownTransition { opid, transition }),
+            };
+            let bundle_id = bundle.bundle_id();
+            let anchor = EAnchor::new(default!(), DbcProof::Opret(OpretProof::default()));
+            let commitment = anchor.convolve(contract_id, bundle_id).unwrap();
+            let tx = Tx {
+                version: Version::TWO,
+                lock_time: LockTime::ZERO,
+                input: rights
+                    .iter()
+                    .map(|right| TxIn {
+                        previous_output: self.outpoints[right],
+                        ..default!()
+                    })
+                    .collect(),
+                output: vec![TxOut {
+                    value: Amount::ZERO,
+                    script_pubkey: ScriptBuf::new_op_return(commitment.to_byte_array()),
+                }],
+            };
+            let witness_id = tx.compute_txid();
+            self.resolver
+                .witnesses
+                .insert(witness_id, WitnessStatus::mempool(tx));
+            self.consignment.bundles.push((bundle, anchor, witness_id));
+            let right = Opout::new(opid, RIGHT, 0);
+            self.outpoints.insert(right, Outpoint::new(witness_id, 0));
+            (bundle_id, right)
+        }
+
+        fn validation_config(&self) -> ValidationConfig {
+            ValidationConfig {
+                chain_net: self.consignment.genesis.chain_net,
+                ..default!()
+            }
+        }
+    }
+
+    fn rights<Seal: ExposedSeal>(seal: Seal) -> Assignments<Seal> {
+        let assign = Assign::revealed(seal, default!());
+        let typed_assigns = TypedAssigns::Declarative(AssignVec::with(NonEmptyVec::with(assign)));
+        Assignments::from(Confined::try_from_iter([(RIGHT, typed_assigns)]).unwrap())
+    }
+
+    /// Resolver serving mempool witnesses and counting the queries it receives.
+    #[derive(Default)]
+    struct CountingResolver {
+        witnesses: BTreeMap<Txid, WitnessStatus>,
+        queries: RefCell<Vec<Txid>>,
+        batches: RefCell<Vec<usize>>,
+    }
+
+    impl CountingResolver {
+        fn with_witnesses(count: u32) -> Self {
+            let witnesses = (0..count)
+                .map(|no| {
+                    let mut tx = Tx::strict_dumb();
+                    tx.lock_time = LockTime::from_consensus(no);
+                    (tx.compute_txid(), WitnessStatus::mempool(tx))
+                })
+                .collect();
+            Self {
+                witnesses,
+                ..default!()
+            }
+        }
+
+        fn with_ord(mut self, ord: WitnessOrd) -> Self {
+            for witness_status in self.witnesses.values_mut() {
+                if let WitnessStatus::Resolved(_, witness_ord) = witness_status {
+                    *witness_ord = ord;
+                }
+            }
+            self
+        }
+
+        fn witness_ids(&self) -> Vec<Txid> { self.witnesses.keys().copied().collect() }
+    }
+
+    impl ResolveWitness for CountingResolver {
+        fn resolve_witness(&self, witness_id: Txid) -> Result<WitnessStatus, WitnessResolverError> {
+            self.queries.borrow_mut().push(witness_id);
+            Ok(self
+                .witnesses
+                .get(&witness_id)
+                .cloned()
+                .unwrap_or_else(WitnessStatus::missing))
+        }
+
+        fn resolve_witnesses(
+            &self,
+            witness_ids: &[Txid],
+        ) -> Result<Vec<WitnessStatus>, WitnessResolverError> {
+            self.batches.borrow_mut().push(witness_ids.len());
+            witness_ids
+                .iter()
+                .map(|witness_id| self.resolve_witness(*witness_id))
+                .collect()
+        }
+
+        fn check_chain_net(&self, _: ChainNet) -> Result<(), WitnessResolverError> { Ok(()) }
+    }
+
+    /// Runs the witness resolution phases of the validation, returning the resulting status.
+    fn resolve_witnesses(
+        consignment: &TestConsignment,
+        resolver: &impl ResolveWitness,
+        validation_config: &ValidationConfig,
+    ) -> Status {
+        let validator =
+            Validator::<NoState, _, _>::init(consignment, resolver, (), validation_config);
+        validator
+            .check_chain_net(validation_config.chain_net)
+            .unwrap();
+        validator.prefetch_witnesses().unwrap();
+        for (bundle, _, witness_id) in consignment.bundles_info() {
+            validator
+                .resolve_witness(bundle.bundle_id(), witness_id)
+                .unwrap();
+        }
+        validator.status.into_inner()
+    }
+
+    #[test]
+    fn replay_reproduces_status() {
+        let (mut contract, right) = TestContract::issue();
+        let (_, right) = contract.transfer(&[right]);
+        let (_, right) = contract.transfer(&[right]);
+        contract.transfer(&[right]);
+        let validation_config = ValidationConfig {
+            safe_height: NonZeroU32::new(1),
+            build_opouts_dag: true,
+            max_concurrent_resolutions: NonZeroUsize::new(2),
+            ..contract.validation_config()
+        };
+
+        let recorder = RecordingResolver::new(&contract.resolver);
+        let recorded = Validator::<NoState, _, _>::validate(
+            &contract.consignment,
+            &recorder,
+            (),
+            &validation_config,
+        )
+        .unwrap();
+        let (_, transcript) = recorder.into_parts();
+        assert_eq!(recorded.tx_ord_map.len(), 3);
+        assert_eq!(recorded.validity(), Validity::ValidButUnsafe);
+
+        let replay = ReplayResolver::new(transcript);
+        let replayed = Validator::<NoState, _, _>::validate(
+            &contract.consignment,
+            &replay,
+            (),
+            &validation_config,
+        )
+        .unwrap();
+        assert!(replay.is_exhausted());
+        assert_eq!(format!("{replayed:?}"), format!("{recorded:?}"));
+    }
+
+    #[test]
+    fn provenance_walks_back_to_genesis() {
+        let (mut contract, issued) = TestContract::issue();
+        let (_, first) = contract.transfer(&[issued]);
+        let (_, second) = contract.transfer(&[first]);
+        let (_, third) = contract.transfer(&[second]);
+        let consignment = CheckedConsignment::new(&contract.consignment);
+        let witness_ids = contract
+            .consignment
+            .bundles
+            .iter()
+            .map(|(_, _, witness_id)| Some(*witness_id))
+            .collect::<Vec<_>>();
+
+        let tree = consignment.provenance(third).unwrap();
+        assert_eq!(tree.root, third.op);
+        assert_eq!(tree.nodes, bmap! {
+            issued.op => ProvenanceNode { witness_id: None, parents: none!() },
+            first.op => ProvenanceNode { witness_id: witness_ids[0], parents: bset! { issued.op } },
+            second.op => ProvenanceNode { witness_id: witness_ids[1], parents: bset! { first.op } },
+            third.op => ProvenanceNode { witness_id: witness_ids[2], parents: bset! { second.op } },
+        });
+
+        let unknown = Opout::new(OpId::strict_dumb(), third.ty, 0);
+        assert_eq!(
+            consignment.provenance(unknown),
+            Err(ProvenanceError::UnknownOperation(unknown.op))
+        );
+
+        let out_of_range = Opout::new(third.op, third.ty, third.no + 1);
+        assert_eq!(
+            consignment.provenance(out_of_range),
+            Err(ProvenanceError::UnknownOpout(third.op, out_of_range))
+        );
+    }
+
+    #[test]
+    fn integrity_rejects_input_map_size_mismatch() {
+        let (mut contract, right) = TestContract::issue();
+        contract.transfer(&[right]);
+        let bundle = &mut contract.consignment.bundles[0].0;
+        let opid = bundle.known_transitions[0].opid;
+        bundle
+            .input_map
+            .insert(Opout::new(right.op, right.ty, right.no + 1), opid)
+            .unwrap();
+        let bundle_id = bundle.bundle_id();
+
+        assert_eq!(
+            Validator::<NoState, _, _>::validate(
+                &contract.consignment,
+                &contract.resolver,
+                (),
+                &contract.validation_config(),
+            )
+            .unwrap_err(),
+            ValidationError::InvalidConsignment(Failure::InputMapSizeMismatch {
+                bundle_id,
+                map_len: 2,
+                inputs_len: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn integrity_rejects_duplicate_opid() {
+        let (mut contract, right) = TestContract::issue();
+        contract.transfer(&[right]);
+        let duplicate = contract.consignment.bundles[0].clone();
+        let opid = duplicate.0.known_transitions[0].opid;
+        contract.consignment.bundles.push(duplicate);
+
+        assert_eq!(
+            Validator::<NoState, _, _>::validate(
+                &contract.consignment,
+                &contract.resolver,
+                (),
+                &contract.validation_config(),
+            )
+            .unwrap_err(),
+            ValidationError::InvalidConsignment(Failure::DuplicateOpId(opid))
+        );
+    }
+
+    #[test]
+    fn integrity_rejects_too_large_consignment() {
+        let (mut contract, right) = TestContract::issue();
+        let (_, right) = contract.transfer(&[right]);
+        contract.transfer(&[right]);
+        let validate = |validation_config: ValidationConfig| {
+            Validator::<NoState, _, _>::validate(
+                &contract.consignment,
+                &contract.resolver,
+                (),
+                &validation_config,
+            )
+        };
+
+        assert_eq!(
+            validate(ValidationConfig {
+                max_bundles: Some(1),
+                ..contract.validation_config()
+            })
+            .unwrap_err(),
+            ValidationError::InvalidConsignment(Failure::ConsignmentTooLarge {
+                kind: ConsignmentItem::Bundles,
+                count: 2,
+                limit: 1,
+            })
+        );
+        assert_eq!(
+            validate(ValidationConfig {
+                max_operations: Some(2),
+                ..contract.validation_config()
+            })
+            .unwrap_err(),
+            ValidationError::InvalidConsignment(Failure::ConsignmentTooLarge {
+                kind: ConsignmentItem::Operations,
+                count: 3,
+                limit: 2,
+            })
+        );
+        assert!(validate(ValidationConfig {
+            max_bundles: Some(2),
+            max_operations: Some(3),
+            ..contract.validation_config()
+        })
+        .is_ok());
+    }
+
+    #[test]
+    fn shared_witnesses_resolved_once() {
+        let resolver = CountingResolver::with_witnesses(4);
+        let witness_ids = resolver.witness_ids();
+        let first = TestConsignment::with_witnesses(&witness_ids[..3]);
+        let second = TestConsignment::with_witnesses(&witness_ids[1..]);
+        let validation_config = ValidationConfig {
+            chain_net: first.genesis.chain_net,
+            ..default!()
+        };
+
+        let caching = CachingResolver::new(&resolver);
+        for consignment in [&first, &second] {
+            let status = resolve_witnesses(consignment, &caching, &validation_config);
+            assert_eq!(status.tx_ord_map.len(), 3);
+        }
+
+        let mut queries = resolver.queries.into_inner();
+        queries.sort();
+        assert_eq!(queries, witness_ids);
+    }
+
+    #[test]
+    fn prefetch_respects_concurrency_cap() {
+        let resolver = CountingResolver::with_witnesses(5);
+        let mut witness_ids = resolver.witness_ids();
+        witness_ids.push(witness_ids[0]);
+        let consignment = TestConsignment::with_witnesses(&witness_ids);
+        let validation_config = ValidationConfig {
+            chain_net: consignment.genesis.chain_net,
+            max_concurrent_resolutions: NonZeroUsize::new(2),
+            ..default!()
+        };
+
+        Validator::<NoState, _, _>::init(&consignment, &resolver, (), &validation_config)
+            .prefetch_witnesses()
+            .unwrap();
+        assert_eq!(resolver.batches.into_inner(), vec![2, 2, 1]);
+        assert_eq!(resolver.queries.into_inner().len(), 5);
+    }
+
+    #[test]
+    fn mined_witness_regression_fails() {
+        let resolver = CountingResolver::with_witnesses(1);
+        let witness_id = resolver.witness_ids()[0];
+        let consignment = TestConsignment::with_witnesses(&[witness_id]);
+        let bundle_id = consignment.bundles[0].0.bundle_id();
+        let mined = WitnessPos::bitcoin(NonZeroU32::new(800_000).unwrap(), 1_700_000_000)
+            .map(WitnessOrd::Mined)
+            .unwrap();
+        let validation_config = ValidationConfig {
+            chain_net: consignment.genesis.chain_net,
+            prior_witness_ords: bmap! { witness_id => mined },
+            ..default!()
+        };
+
+        for resolver in [CountingResolver::default(), resolver.with_ord(WitnessOrd::Archived)] {
+            let validator =
+                Validator::<NoState, _, _>::init(&consignment, &resolver, (), &validation_config);
+            assert_eq!(
+                validator
+                    .resolve_witness(bundle_id, witness_id)
+                    .unwrap_err(),
+                ValidationError::InvalidConsignment(Failure::WitnessOrdRegression(
+                    bundle_id,
+                    witness_id,
+                    mined,
+                    WitnessOrd::Archived
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn subset_resolves_only_validated_witnesses() {
+        let (mut contract, right) = TestContract::issue();
+        let (_, right) = contract.transfer(&[right]);
+        let (bundle_id, _) = contract.transfer(&[right]);
+        let trusted_id = contract.consignment.bundles[0].2;
+        let validated_id = contract.consignment.bundles[1].2;
+        let validation_config = ValidationConfig {
+            prior_witness_ords: bmap! { trusted_id => WitnessOrd::Tentative },
+            build_opouts_dag: true,
+            ..contract.validation_config()
+        };
+
+        let status = Validator::<NoState, _, _>::validate_bundle_subset(
+            &contract.consignment,
+            &contract.resolver,
+            (),
+            &validation_config,
+            &bset![bundle_id],
+        )
+        .unwrap();
+        assert_eq!(contract.resolver.queries.into_inner(), vec![validated_id]);
+        assert_eq!(status.spent_opouts.len(), 2);
+        let (dag, _) = status.dag_data_opt.unwrap();
+        assert_eq!(dag.edge_count(), 2);
+    }
+
+    #[test]
+    fn subset_rejects_double_spend_of_trusted_input() {
+        let (mut contract, right) = TestContract::issue();
+        let (trusted, _) = contract.transfer(&[right]);
+        let (validated, _) = contract.transfer(&[right]);
+        let validated_opid = contract.consignment.bundles[1].0.known_transitions[0].opid;
+        let validation_config = contract.validation_config();
+
+        assert_eq!(
+            Validator::<NoState, _, _>::validate_bundle_subset(
+                &contract.consignment,
+                &contract.resolver,
+                (),
+                &validation_config,
+                &bset![validated],
+            )
+            .unwrap_err(),
+            ValidationError::InvalidConsignment(Failure::NoPrevState(validated_opid, right))
+        );
+        assert_eq!(
+            Validator::<NoState, _, _>::validate_bundle_subset(
+                &contract.consignment,
+                &contract.resolver,
+                (),
+                &validation_config,
+                &bset![trusted],
+            )
+            .unwrap_err(),
+            ValidationError::InvalidConsignment(Failure::CyclicGraph(right))
+        );
+    }
+}